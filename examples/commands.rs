@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use kook::commands::*;
+use kook::prelude::*;
+use std::time::Duration;
+use tracing::metadata::LevelFilter;
+use tracing_subscriber::prelude::*;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer().with_filter(
+                tracing_subscriber::filter::targets::Targets::new()
+                    .with_default(LevelFilter::INFO)
+                    .with_targets([(KOOK, LevelFilter::TRACE)]),
+            ),
+        )
+        .init();
+    let config = Config::load_from_file();
+    let commands = Commands::new()
+        .prefix("/")
+        .command(
+            Command::new("echo", Echo)
+                .alias("e")
+                .usage("<text>")
+                .description("repeat the text"),
+        )
+        .command(
+            Command::new("mute", Mute)
+                .usage("<@user> <duration>")
+                .description("mute a user for a while"),
+        );
+    let kook = Kook::new_from_config(config, commands).arc();
+    kook.start_ws().await.unwrap();
+}
+
+pub struct Echo;
+
+#[async_trait]
+impl CommandHandler for Echo {
+    async fn call(&self, ctx: &Context, _: &Event<EventExtra>, mut args: Args) -> CommandResult {
+        ctx.reply_with(MessageType::Text, &args.rest()).await?;
+        Ok(())
+    }
+}
+
+pub struct Mute;
+
+#[async_trait]
+impl CommandHandler for Mute {
+    async fn call(&self, ctx: &Context, _: &Event<EventExtra>, mut args: Args) -> CommandResult {
+        let user: UserMention = args.next("user")?;
+        let duration: Duration = args.next("duration")?;
+        let content = format!("(met){}(met) muted for {}s", user.0, duration.as_secs());
+        ctx.reply(&content).await?;
+        Ok(())
+    }
+}
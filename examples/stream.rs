@@ -0,0 +1,34 @@
+use futures_util::StreamExt;
+use kook::prelude::*;
+use tracing::metadata::LevelFilter;
+use tracing_subscriber::prelude::*;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer().with_filter(
+                tracing_subscriber::filter::targets::Targets::new()
+                    .with_default(LevelFilter::INFO)
+                    .with_targets([(KOOK, LevelFilter::TRACE)]),
+            ),
+        )
+        .init();
+    let config = Config::load_from_file();
+    let kook = Kook::new_from_config(config, Router::new()).arc();
+    let mut messages = kook
+        .group_messages()
+        .filter(|event| std::future::ready(event.content.starts_with("echo")));
+    let echo = {
+        let kook = kook.clone();
+        tokio::spawn(async move {
+            while let Some(event) = messages.next().await {
+                kook.create_message(None, &event.target_id, "echo", None, None, None)
+                    .await
+                    .ok();
+            }
+        })
+    };
+    kook.start_ws().await.unwrap();
+    echo.abort();
+}
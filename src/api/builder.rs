@@ -1,6 +1,6 @@
 use crate::{
     net::http::QueryBuilder,
-    prelude::{ChannelShort, GuildUserList, KookResult, RespList},
+    prelude::{ChannelKind, ChannelShort, GuildUserList, KookResult, RespList},
 };
 
 use super::{CHANNEL, GUILD};
@@ -32,6 +32,7 @@ impl crate::Kook {
 
 macro_rules! set_fn {
     ($fname: ident, $attr: ident: $aty: ty) => {
+        #[allow(clippy::wrong_self_convention)]
         pub fn $fname(mut self, $attr: $aty) -> Self {
             self.0.push(stringify!($attr), $attr);
             self
@@ -62,8 +63,8 @@ impl<'a> GuildUserListGetter<'a> {
 pub struct CreateChannelPoster<'a>(QueryBuilder, &'a crate::Kook);
 
 impl<'a> CreateChannelPoster<'a> {
-    pub fn ty(mut self, ty: u8) -> Self {
-        self.0.push("type", ty);
+    pub fn ty(mut self, ty: ChannelKind) -> Self {
+        self.0.push("type", u8::from(ty));
         self
     }
     set_fn!(parent_id, parent_id: &str);
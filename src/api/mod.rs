@@ -1,6 +1,6 @@
-use crate::error::KookResult;
+use crate::error::{KookError, KookResult};
 use crate::net::http::QueryBuilder;
-use crate::{objects::*, structs::*, Kook};
+use crate::{objects::*, structs::*, Kook, MessageType};
 
 mod builder;
 
@@ -12,6 +12,7 @@ macro_rules! http_api {
         }
     };
     ($fn_name: ident -> $rty: ty, $method: ident, $url: expr, $($key: ident: $kty: ty),*) => {
+        #[allow(clippy::too_many_arguments)]
         pub async fn $fn_name(&self, $($key: $kty),*) -> KookResult<$rty> {
             let mut query = QueryBuilder::default();
             $(
@@ -30,11 +31,24 @@ impl crate::Kook {
         page: Option<i32>,
         page_size: Option<i32>,
         sort: Option<&str>);
-    http_api!(get_guild_view -> Guild,
-        get, [GUILD, "view"],
-        guild_id: &str);
-    http_api!(get_guild_user_list -> GuildUserList,
-        get, [GUILD, "user-list"],
+    /// Served from the cache when enabled.
+    pub async fn get_guild_view(&self, guild_id: &str) -> KookResult<Guild> {
+        if let Some(cache) = self.cache() {
+            if let Some(guild) = cache.guild(guild_id).await {
+                return Ok(guild);
+            }
+        }
+        let mut query = QueryBuilder::default();
+        query.push("guild_id", guild_id);
+        let guild: Guild = self.get([GUILD, "view"], query).await?;
+        if let Some(cache) = self.cache() {
+            cache.insert_guild(guild.clone()).await;
+        }
+        Ok(guild)
+    }
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_guild_user_list(
+        &self,
         guild_id: &str,
         channel_id: Option<&str>,
         search: Option<&str>,
@@ -44,7 +58,52 @@ impl crate::Kook {
         joined_at: bool,
         page: Option<i32>,
         page_size: Option<i32>,
-        filter_user_id: Option<&str>);
+        filter_user_id: Option<&str>,
+    ) -> KookResult<GuildUserList> {
+        let mut query = QueryBuilder::default();
+        query.push("guild_id", guild_id);
+        query.push("channel_id", channel_id);
+        query.push("search", search);
+        query.push("role_id", role_id);
+        query.push("mobile_verified", mobile_verified);
+        query.push("active_time", active_time);
+        query.push("joined_at", joined_at);
+        query.push("page", page);
+        query.push("page_size", page_size);
+        query.push("filter_user_id", filter_user_id);
+        let list: GuildUserList = self.get([GUILD, "user-list"], query).await?;
+        if let Some(cache) = self.cache() {
+            for user in &list.items {
+                cache.insert_member(guild_id, user.clone()).await;
+            }
+        }
+        Ok(list)
+    }
+    /// A single member of a guild, served from the cache when enabled.
+    pub async fn get_guild_member(&self, guild_id: &str, user_id: &str) -> KookResult<User> {
+        if let Some(cache) = self.cache() {
+            if let Some(user) = cache.member(guild_id, user_id).await {
+                return Ok(user);
+            }
+        }
+        self.get_guild_user_list(
+            guild_id,
+            None,
+            None,
+            None,
+            false,
+            false,
+            false,
+            None,
+            None,
+            Some(user_id),
+        )
+        .await?
+        .items
+        .into_iter()
+        .next()
+        .ok_or(KookError::HttpApiCallEmptyResponse)
+    }
     http_api!(set_guild_user_nickname -> (),
         empty_post,  [GUILD, "nickname"],
         guild_id: &str,
@@ -87,24 +146,37 @@ impl Kook {
         page: Option<i64>,
         page_size: Option<i64>,
         guild_id: &str,
-        ty: Option<u8>,
+        ty: Option<ChannelKind>,
     ) -> KookResult<RespList<ChannelShort>> {
         let mut query = QueryBuilder::default();
         query.push("page", page);
         query.push("page_size", page_size);
         query.push("guild_id", guild_id);
-        query.push("type", ty);
+        query.push("type", ty.map(u8::from));
         self.get([CHANNEL, "list"], query).await
     }
-    http_api!(get_channel_view -> ChannelView,
-        get, [CHANNEL, "view"],
-        target_id: &str);
+    /// Served from the cache when enabled.
+    pub async fn get_channel_view(&self, target_id: &str) -> KookResult<ChannelView> {
+        if let Some(cache) = self.cache() {
+            if let Some(view) = cache.channel_view(target_id).await {
+                return Ok(view);
+            }
+        }
+        let mut query = QueryBuilder::default();
+        query.push("target_id", target_id);
+        let view: ChannelView = self.get([CHANNEL, "view"], query).await?;
+        if let Some(cache) = self.cache() {
+            cache.insert_channel_view(view.clone()).await;
+        }
+        Ok(view)
+    }
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_channel(
         &self,
         guild_id: &str,
         parent_id: Option<&str>,
         name: &str,
-        ty: Option<u8>,
+        ty: Option<ChannelKind>,
         limit_amount: Option<i64>,
         voice_quality: Option<&str>,
         is_category: Option<i64>,
@@ -113,7 +185,7 @@ impl Kook {
         query.push("guild_id", guild_id);
         query.push("parent_id", parent_id);
         query.push("name", name);
-        query.push("type", ty);
+        query.push("type", ty.map(u8::from));
         query.push("limit_amount", limit_amount);
         query.push("voice_quality", voice_quality);
         query.push("is_category", is_category);
@@ -150,7 +222,7 @@ const MESSAGE: &str = "message";
 impl Kook {
     pub async fn create_message(
         &self,
-        ty: Option<u8>,
+        ty: Option<MessageType>,
         target_id: &str,
         content: &str,
         quote: Option<&str>,
@@ -160,7 +232,7 @@ impl Kook {
         let mut query = QueryBuilder::default();
         query.push("target_id", target_id);
         query.push("content", content);
-        query.push("type", ty);
+        query.push("type", ty.map(u8::from));
         query.push("quote", quote);
         query.push("nonce", nonce);
         query.push("temp_target_id", temp_target_id);
@@ -175,6 +247,15 @@ impl Kook {
     http_api!(delete_message -> (),
         empty_post,  [MESSAGE, "delete"],
         msg_id: &str);
+    http_api!(add_message_reaction -> (),
+        empty_post,  [MESSAGE, "add-reaction"],
+        msg_id: &str,
+        emoji: &str);
+    http_api!(delete_message_reaction -> (),
+        empty_post,  [MESSAGE, "delete-reaction"],
+        msg_id: &str,
+        emoji: &str,
+        user_id: Option<&str>);
 }
 
 const DIRECT_MESSAGE: &str = "direct-message";
@@ -193,7 +274,7 @@ impl crate::Kook {
         target_id: Option<&str>,
         chat_code: Option<&str>,
         content: &str,
-        ty: Option<u8>,
+        ty: Option<MessageType>,
         quote: Option<&str>,
         nonce: Option<&str>,
     ) -> KookResult<MessageResp> {
@@ -201,7 +282,7 @@ impl crate::Kook {
         query.push("target_id", target_id);
         query.push("chat_code", chat_code);
         query.push("content", content);
-        query.push("type", ty);
+        query.push("type", ty.map(u8::from));
         query.push("quote", quote);
         query.push("nonce", nonce);
         self.post([DIRECT_MESSAGE, "create"], query).await
@@ -215,8 +296,14 @@ impl crate::Kook {
         empty_post,  [DIRECT_MESSAGE, "delete"],
         msg_id: Option<&str>);
     // http_api!(get_direct_message_reaction_list) todo
-    // http_api!(add_direct_message_reaction) todo
-    // http_api!(delete_direct_message_reaction) todo
+    http_api!(add_direct_message_reaction -> (),
+        empty_post,  [DIRECT_MESSAGE, "add-reaction"],
+        msg_id: &str,
+        emoji: &str);
+    http_api!(delete_direct_message_reaction -> (),
+        empty_post,  [DIRECT_MESSAGE, "delete-reaction"],
+        msg_id: &str,
+        emoji: &str);
 }
 
 const USER: &str = "user";
@@ -0,0 +1,354 @@
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// Storage behind `Cache`, values are JSON encoded entities.
+///
+/// Implement this to keep the cache in an external store; `MemoryBackend`
+/// can stand in for it in tests.
+#[async_trait]
+pub trait CacheBackend: Sync + Send {
+    async fn get(&self, key: &str) -> Option<Vec<u8>>;
+    async fn set(&self, key: &str, value: Vec<u8>);
+    async fn remove(&self, key: &str);
+    /// All live keys starting with `prefix`.
+    async fn keys(&self, prefix: &str) -> Vec<String>;
+
+    async fn clear(&self) {
+        for key in self.keys("").await {
+            self.remove(&key).await;
+        }
+    }
+}
+
+/// Expiry and size limits shared by the bundled backends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// Entries older than this are treated as missing.
+    pub ttl: Option<Duration>,
+    /// The oldest entries are evicted beyond this count.
+    pub max_entries: Option<usize>,
+}
+
+struct MemoryEntry {
+    value: Vec<u8>,
+    inserted: Instant,
+    seq: u64,
+}
+
+/// Keys in insertion order, so the oldest entries are found without a scan.
+#[derive(Default)]
+struct InsertOrder {
+    next: u64,
+    keys: BTreeMap<u64, String>,
+}
+
+#[derive(Default)]
+pub struct MemoryBackend {
+    entries: DashMap<String, MemoryEntry>,
+    order: Mutex<InsertOrder>,
+    limits: CacheLimits,
+}
+
+impl MemoryBackend {
+    pub fn new(limits: CacheLimits) -> Self {
+        Self {
+            entries: DashMap::default(),
+            order: Mutex::default(),
+            limits,
+        }
+    }
+
+    fn expired(&self, entry: &MemoryEntry) -> bool {
+        self.limits
+            .ttl
+            .is_some_and(|ttl| entry.inserted.elapsed() > ttl)
+    }
+
+    fn order(&self) -> MutexGuard<'_, InsertOrder> {
+        self.order.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drop expired entries and the oldest ones beyond `max_entries`.
+    fn evict(&self, order: &mut InsertOrder) {
+        let max = self.limits.max_entries.unwrap_or(usize::MAX);
+        while let Some(entry) = order.keys.first_entry() {
+            let evict = self.entries.len() > max
+                || self
+                    .entries
+                    .get(entry.get())
+                    .is_none_or(|e| self.expired(&e));
+            if !evict {
+                break;
+            }
+            self.entries.remove(&entry.remove());
+        }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryBackend {
+    async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let entry = self.entries.get(key)?;
+        if self.expired(&entry) {
+            drop(entry);
+            self.remove(key).await;
+            return None;
+        }
+        Some(entry.value.clone())
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>) {
+        let mut order = self.order();
+        let seq = order.next;
+        order.next += 1;
+        order.keys.insert(seq, key.to_owned());
+        let entry = MemoryEntry {
+            value,
+            inserted: Instant::now(),
+            seq,
+        };
+        if let Some(old) = self.entries.insert(key.to_owned(), entry) {
+            order.keys.remove(&old.seq);
+        }
+        self.evict(&mut order);
+    }
+
+    async fn remove(&self, key: &str) {
+        let mut order = self.order();
+        if let Some((_, old)) = self.entries.remove(key) {
+            order.keys.remove(&old.seq);
+        }
+    }
+
+    async fn keys(&self, prefix: &str) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| e.key().starts_with(prefix) && !self.expired(e.value()))
+            .map(|e| e.key().clone())
+            .collect()
+    }
+
+    async fn clear(&self) {
+        let mut order = self.order();
+        self.entries.clear();
+        order.keys.clear();
+    }
+}
+
+/// A cache kept in an SQLite file, several processes may open the same file.
+#[cfg(feature = "rusqlite")]
+pub struct SqliteBackend {
+    conn: std::sync::Arc<Mutex<rusqlite::Connection>>,
+    limits: CacheLimits,
+    /// Writes since opening, expired and excess rows are deleted every `EVICT_EVERY`.
+    writes: std::sync::atomic::AtomicUsize,
+}
+
+/// Reads skip expired rows anyway, so limits are enforced in batches.
+#[cfg(feature = "rusqlite")]
+const EVICT_EVERY: usize = 64;
+
+#[cfg(feature = "rusqlite")]
+impl SqliteBackend {
+    pub fn open<P: AsRef<std::path::Path>>(path: P, limits: CacheLimits) -> rusqlite::Result<Self> {
+        Self::from_connection(rusqlite::Connection::open(path)?, limits)
+    }
+
+    pub fn open_in_memory(limits: CacheLimits) -> rusqlite::Result<Self> {
+        Self::from_connection(rusqlite::Connection::open_in_memory()?, limits)
+    }
+
+    fn from_connection(conn: rusqlite::Connection, limits: CacheLimits) -> rusqlite::Result<Self> {
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS kook_cache (
+                key TEXT PRIMARY KEY,
+                value BLOB NOT NULL,
+                inserted INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS kook_cache_inserted ON kook_cache (inserted);",
+        )?;
+        Ok(Self {
+            conn: std::sync::Arc::new(Mutex::new(conn)),
+            limits,
+            writes: std::sync::atomic::AtomicUsize::default(),
+        })
+    }
+
+    /// Oldest insert time still alive, in milliseconds.
+    fn min_inserted(&self) -> i64 {
+        match self.limits.ttl {
+            Some(ttl) => crate::prelude::Timestamp::now().as_millis() - ttl.as_millis() as i64,
+            None => i64::MIN,
+        }
+    }
+
+    /// Run `f` on the blocking thread pool, as it may wait on the file lock.
+    async fn run<T, F>(&self, f: F) -> Option<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&rusqlite::Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+            f(&conn)
+        })
+        .await;
+        match result {
+            Ok(Ok(t)) => Some(t),
+            Ok(Err(e)) => {
+                tracing::warn!(target: crate::KOOK, "sqlite cache error: {}", e);
+                None
+            }
+            Err(e) => {
+                tracing::warn!(target: crate::KOOK, "sqlite cache task failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+#[async_trait]
+impl CacheBackend for SqliteBackend {
+    async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        use rusqlite::OptionalExtension;
+        let key = key.to_owned();
+        let min = self.min_inserted();
+        self.run(move |conn| {
+            conn.query_row(
+                "SELECT value FROM kook_cache WHERE key = ?1 AND inserted >= ?2",
+                rusqlite::params![key, min],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+        .await
+        .flatten()
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>) {
+        let now = crate::prelude::Timestamp::now().as_millis();
+        let writes = self
+            .writes
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let evict = (writes + 1).is_multiple_of(EVICT_EVERY);
+        let ttl = self.limits.ttl.map(|_| self.min_inserted());
+        let max = self.limits.max_entries;
+        let key = key.to_owned();
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO kook_cache (key, value, inserted) VALUES (?1, ?2, ?3)",
+                rusqlite::params![key, value, now],
+            )?;
+            if !evict {
+                return Ok(());
+            }
+            if let Some(min) = ttl {
+                conn.execute("DELETE FROM kook_cache WHERE inserted < ?1", [min])?;
+            }
+            if let Some(max) = max {
+                let count: i64 =
+                    conn.query_row("SELECT COUNT(*) FROM kook_cache", [], |row| row.get(0))?;
+                if count > max as i64 {
+                    conn.execute(
+                        "DELETE FROM kook_cache WHERE key IN (
+                            SELECT key FROM kook_cache
+                            ORDER BY inserted DESC, rowid DESC LIMIT -1 OFFSET ?1
+                        )",
+                        [max as i64],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+        .await;
+    }
+
+    async fn remove(&self, key: &str) {
+        let key = key.to_owned();
+        self.run(move |conn| conn.execute("DELETE FROM kook_cache WHERE key = ?1", [key]))
+            .await;
+    }
+
+    async fn keys(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_owned();
+        let min = self.min_inserted();
+        self.run(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT key FROM kook_cache WHERE substr(key, 1, length(?1)) = ?1 AND inserted >= ?2",
+            )?;
+            let keys = stmt
+                .query_map(rusqlite::params![prefix, min], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            Ok(keys)
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn clear(&self) {
+        self.run(|conn| conn.execute("DELETE FROM kook_cache", []))
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn memory_backend_test() {
+    let backend = MemoryBackend::new(CacheLimits {
+        ttl: None,
+        max_entries: Some(2),
+    });
+    backend.set("a", b"1".to_vec()).await;
+    backend.set("b", b"2".to_vec()).await;
+    backend.set("c", b"3".to_vec()).await;
+    assert_eq!(backend.get("a").await, None);
+    assert_eq!(backend.get("c").await, Some(b"3".to_vec()));
+    let mut keys = backend.keys("").await;
+    keys.sort();
+    assert_eq!(keys, vec!["b", "c"]);
+    // setting a key again makes it the newest
+    backend.set("b", b"2".to_vec()).await;
+    backend.set("d", b"4".to_vec()).await;
+    assert_eq!(backend.get("c").await, None);
+    assert_eq!(backend.get("b").await, Some(b"2".to_vec()));
+    assert_eq!(backend.order().keys.len(), 2);
+
+    let backend = MemoryBackend::new(CacheLimits {
+        ttl: Some(Duration::from_millis(10)),
+        max_entries: None,
+    });
+    backend.set("a", b"1".to_vec()).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(backend.get("a").await, None);
+    assert!(backend.keys("a").await.is_empty());
+}
+
+#[cfg(feature = "rusqlite")]
+#[tokio::test]
+async fn sqlite_backend_test() {
+    let backend = SqliteBackend::open_in_memory(CacheLimits {
+        ttl: None,
+        max_entries: Some(2),
+    })
+    .unwrap();
+    for i in 0..EVICT_EVERY - 1 {
+        backend.set(&format!("member:{}", i), b"1".to_vec()).await;
+    }
+    // nothing is evicted until the batch is full
+    assert!(backend.get("member:0").await.is_some());
+    backend.set("user:1", b"1".to_vec()).await;
+    assert_eq!(backend.get("member:0").await, None);
+    let last = format!("member:{}", EVICT_EVERY - 2);
+    assert_eq!(backend.keys("member:").await, vec![last.clone()]);
+    backend.remove(&last).await;
+    assert_eq!(backend.get(&last).await, None);
+    backend.clear().await;
+    assert!(backend.keys("").await.is_empty());
+}
@@ -0,0 +1,208 @@
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/// A message as last seen by the `MessageCache`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedMessage {
+    pub msg_id: String,
+    /// The channel id, or the chat code for direct messages.
+    pub channel_id: String,
+    pub author: User,
+    #[serde(rename = "type")]
+    pub ty: MessageType,
+    pub content: String,
+    pub msg_timestamp: Timestamp,
+}
+
+/// Keeps the last messages of every channel and direct chat so edits and
+/// deletes can be shown with the content from before.
+///
+/// Enable with `Kook::with_message_cache`; the state before the event is
+/// then available as `Context::previous_message`.
+#[derive(Debug)]
+pub struct MessageCache {
+    per_channel: usize,
+    channels: DashMap<String, VecDeque<CachedMessage>>,
+}
+
+impl Default for MessageCache {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl MessageCache {
+    /// Keep at most `per_channel` messages for each channel.
+    pub fn new(per_channel: usize) -> Self {
+        Self {
+            per_channel,
+            channels: DashMap::default(),
+        }
+    }
+
+    pub fn get(&self, channel_id: &str, msg_id: &str) -> Option<CachedMessage> {
+        self.channels
+            .get(channel_id)?
+            .iter()
+            .find(|m| m.msg_id == msg_id)
+            .cloned()
+    }
+
+    /// Cached messages of a channel, oldest first.
+    pub fn channel_messages(&self, channel_id: &str) -> Vec<CachedMessage> {
+        self.channels
+            .get(channel_id)
+            .map(|messages| messages.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn push(&self, message: CachedMessage) {
+        if self.per_channel == 0 {
+            return;
+        }
+        let mut messages = self.channels.entry(message.channel_id.clone()).or_default();
+        if messages.len() >= self.per_channel {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+    }
+
+    fn edit(&self, channel_id: &str, msg_id: &str, content: &str) -> Option<CachedMessage> {
+        let mut messages = self.channels.get_mut(channel_id)?;
+        let message = messages.iter_mut().find(|m| m.msg_id == msg_id)?;
+        let previous = message.clone();
+        message.content = content.to_owned();
+        Some(previous)
+    }
+
+    fn remove(&self, channel_id: &str, msg_id: &str) -> Option<CachedMessage> {
+        let mut messages = self.channels.get_mut(channel_id)?;
+        let index = messages.iter().position(|m| m.msg_id == msg_id)?;
+        messages.remove(index)
+    }
+
+    /// Record `event`, returns the cached message from before an edit or delete.
+    pub(crate) fn update(&self, event: &Event<EventExtra>) -> Option<CachedMessage> {
+        let (channel_id, author) = match &event.extra {
+            EventExtra::GroupMessage(g) => (event.target_id.clone(), g.author.clone()),
+            EventExtra::PersonMessage(p) => (p.code.clone(), p.author.clone()),
+            EventExtra::System(SystemExtra::UpdatedMessage(m)) => {
+                return self.edit(&m.channel_id, &m.msg_id, &m.content)
+            }
+            EventExtra::System(SystemExtra::UpdatedPrivateMessage(m)) => {
+                return self.edit(&m.chat_code, &m.msg_id, &m.content)
+            }
+            EventExtra::System(SystemExtra::DeletedMessage(m)) => {
+                return self.remove(&m.channel_id, &m.msg_id)
+            }
+            EventExtra::System(SystemExtra::DeletedPrivateMessage(m)) => {
+                return self.remove(&m.chat_code, &m.msg_id)
+            }
+            EventExtra::System(SystemExtra::DeletedChannel(c)) => {
+                self.channels.remove(&c.id);
+                return None;
+            }
+            EventExtra::System(_) => return None,
+        };
+        self.push(CachedMessage {
+            msg_id: event.msg_id.clone(),
+            channel_id,
+            author,
+            ty: event.ty,
+            content: event.content.clone(),
+            msg_timestamp: event.msg_timestamp,
+        });
+        None
+    }
+}
+
+impl Kook {
+    pub fn with_message_cache(mut self, cache: MessageCache) -> Self {
+        self.message_cache = Some(cache);
+        self
+    }
+
+    pub fn message_cache(&self) -> Option<&MessageCache> {
+        self.message_cache.as_ref()
+    }
+}
+
+#[test]
+fn message_cache_test() {
+    let cache = MessageCache::new(2);
+    let mut event = crate::test::group_message("first");
+    for (id, content) in [("1", "first"), ("2", "second"), ("3", "third")] {
+        event.msg_id = id.to_owned();
+        event.content = content.to_owned();
+        assert!(cache.update(&event).is_none());
+    }
+    assert!(cache.get("channel", "1").is_none());
+    assert_eq!(cache.channel_messages("channel").len(), 2);
+
+    let edit = crate::test::system_event(
+        "guild",
+        serde_json::json!({
+            "type": "updated_message",
+            "body": {
+                "channel_id": "channel",
+                "content": "edited",
+                "mention": [],
+                "mention_all": false,
+                "mention_here": false,
+                "mention_roles": [],
+                "updated_at": 1612778254192u64,
+                "msg_id": "2"
+            }
+        }),
+    );
+    assert_eq!(cache.update(&edit).unwrap().content, "second");
+    assert_eq!(cache.get("channel", "2").unwrap().content, "edited");
+
+    let mut delete = edit;
+    delete.extra = EventExtra::System(SystemExtra::DeletedMessage(DeletedMessage {
+        channel_id: "channel".to_owned(),
+        msg_id: "2".to_owned(),
+    }));
+    let deleted = cache.update(&delete).unwrap();
+    assert_eq!(deleted.content, "edited");
+    assert_eq!(deleted.author.id, "user");
+    assert!(cache.get("channel", "2").is_none());
+}
+
+#[tokio::test]
+async fn previous_message_test() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Default, Clone)]
+    struct Deleted(Arc<Mutex<Option<String>>>);
+
+    #[async_trait::async_trait]
+    impl EventHandler for Deleted {
+        async fn handle(&self, ctx: &Context, _event: Event<EventExtra>) -> HandlerResult {
+            if let Some(previous) = &ctx.previous_message {
+                *self.0.lock().unwrap() = Some(previous.content.clone());
+            }
+            Ok(())
+        }
+    }
+
+    let deleted = Deleted::default();
+    let kook = Kook::new_from_config(Config::default(), deleted.clone())
+        .with_message_cache(MessageCache::new(10))
+        .arc();
+    kook.submit(crate::test::group_message("hello"));
+    let delete = crate::test::system_event(
+        "guild",
+        serde_json::json!({
+            "type": "deleted_message",
+            "body": { "channel_id": "channel", "msg_id": "xxx" }
+        }),
+    );
+    kook.submit(delete);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(deleted.0.lock().unwrap().as_deref(), Some("hello"));
+}
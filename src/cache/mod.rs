@@ -0,0 +1,480 @@
+mod backend;
+mod message;
+
+pub use backend::*;
+pub use message::*;
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Guilds, channels, members and users seen through the API, kept up to
+/// date from gateway events. Enable with `Kook::with_cache`.
+///
+/// Entries are stored under `guild:{id}`, `channel:{id}`, `channel_view:{id}`,
+/// `member:{guild_id}:{user_id}` and `user:{id}` in the backend, with the
+/// guild ids of each cached member under `member_of:{user_id}`.
+#[derive(Clone)]
+pub struct Cache {
+    backend: Arc<dyn CacheBackend>,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::with_backend(MemoryBackend::default())
+    }
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_backend<B>(backend: B) -> Self
+    where
+        B: CacheBackend + 'static,
+    {
+        Self {
+            backend: Arc::new(backend),
+        }
+    }
+
+    pub fn backend(&self) -> &dyn CacheBackend {
+        self.backend.as_ref()
+    }
+
+    async fn load<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let bytes = self.backend.get(key).await?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    async fn store<T: Serialize>(&self, key: &str, value: &T) {
+        if let Ok(bytes) = serde_json::to_vec(value) {
+            self.backend.set(key, bytes).await
+        }
+    }
+
+    /// Like `store` but skips the write when the entry is unchanged, for
+    /// entities seen on every message. Returns whether it was written.
+    async fn store_changed<T: Serialize>(&self, key: &str, value: &T) -> bool {
+        let bytes = match serde_json::to_vec(value) {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        if self.backend.get(key).await.as_ref() == Some(&bytes) {
+            return false;
+        }
+        self.backend.set(key, bytes).await;
+        true
+    }
+
+    pub async fn guild(&self, guild_id: &str) -> Option<Guild> {
+        self.load(&format!("guild:{}", guild_id)).await
+    }
+
+    pub async fn channel(&self, channel_id: &str) -> Option<Channel> {
+        self.load(&format!("channel:{}", channel_id)).await
+    }
+
+    pub async fn channel_view(&self, channel_id: &str) -> Option<ChannelView> {
+        self.load(&format!("channel_view:{}", channel_id)).await
+    }
+
+    pub async fn role(&self, guild_id: &str, role_id: i32) -> Option<Role> {
+        self.guild(guild_id)
+            .await?
+            .roles
+            .into_iter()
+            .find(|r| r.role_id == role_id)
+    }
+
+    pub async fn member(&self, guild_id: &str, user_id: &str) -> Option<User> {
+        self.load(&format!("member:{}:{}", guild_id, user_id)).await
+    }
+
+    pub async fn user(&self, user_id: &str) -> Option<User> {
+        self.load(&format!("user:{}", user_id)).await
+    }
+
+    pub async fn insert_guild(&self, guild: Guild) {
+        for channel in &guild.channels {
+            self.store(&format!("channel:{}", channel.id), channel)
+                .await;
+        }
+        self.store(&format!("guild:{}", guild.id), &guild).await;
+    }
+
+    pub async fn insert_channel_view(&self, view: ChannelView) {
+        self.store(&format!("channel_view:{}", view.id), &view)
+            .await;
+    }
+
+    pub async fn insert_member(&self, guild_id: &str, user: User) {
+        self.store_changed(&format!("user:{}", user.id), &user)
+            .await;
+        self.store_member(guild_id, &user).await;
+    }
+
+    async fn store_member(&self, guild_id: &str, user: &User) {
+        let key = format!("member:{}:{}", guild_id, user.id);
+        if !self.store_changed(&key, user).await {
+            // unchanged, so already indexed
+            return;
+        }
+        let key = format!("member_of:{}", user.id);
+        let mut guilds: Vec<String> = self.load(&key).await.unwrap_or_default();
+        if !guilds.iter().any(|g| g == guild_id) {
+            guilds.push(guild_id.to_owned());
+            self.store(&key, &guilds).await;
+        }
+    }
+
+    async fn remove_member(&self, guild_id: &str, user_id: &str) {
+        self.backend
+            .remove(&format!("member:{}:{}", guild_id, user_id))
+            .await;
+        let key = format!("member_of:{}", user_id);
+        if let Some(mut guilds) = self.load::<Vec<String>>(&key).await {
+            guilds.retain(|g| g != guild_id);
+            if guilds.is_empty() {
+                self.backend.remove(&key).await;
+            } else {
+                self.store(&key, &guilds).await;
+            }
+        }
+    }
+
+    pub async fn clear(&self) {
+        self.backend.clear().await
+    }
+
+    async fn upsert_channel(&self, channel: Channel) {
+        self.backend
+            .remove(&format!("channel_view:{}", channel.id))
+            .await;
+        if let Some(mut guild) = self.guild(&channel.guild_id).await {
+            match guild.channels.iter_mut().find(|c| c.id == channel.id) {
+                Some(c) => *c = channel.clone(),
+                None => guild.channels.push(channel.clone()),
+            }
+            self.store(&format!("guild:{}", guild.id), &guild).await;
+        }
+        self.store(&format!("channel:{}", channel.id), &channel)
+            .await;
+    }
+
+    async fn remove_channel(&self, channel_id: &str) {
+        self.backend
+            .remove(&format!("channel_view:{}", channel_id))
+            .await;
+        if let Some(channel) = self.channel(channel_id).await {
+            self.backend
+                .remove(&format!("channel:{}", channel_id))
+                .await;
+            if let Some(mut guild) = self.guild(&channel.guild_id).await {
+                guild.channels.retain(|c| c.id != channel_id);
+                self.store(&format!("guild:{}", guild.id), &guild).await;
+            }
+        }
+    }
+
+    async fn update_roles<F>(&self, guild_id: &str, f: F)
+    where
+        F: FnOnce(&mut Vec<Role>),
+    {
+        if let Some(mut guild) = self.guild(guild_id).await {
+            f(&mut guild.roles);
+            self.store(&format!("guild:{}", guild.id), &guild).await;
+        }
+    }
+
+    async fn remove_guild(&self, guild_id: &str) {
+        if let Some(guild) = self.guild(guild_id).await {
+            for channel in &guild.channels {
+                self.backend
+                    .remove(&format!("channel:{}", channel.id))
+                    .await;
+                self.backend
+                    .remove(&format!("channel_view:{}", channel.id))
+                    .await;
+            }
+        }
+        self.backend.remove(&format!("guild:{}", guild_id)).await;
+        let prefix = format!("member:{}:", guild_id);
+        for key in self.backend.keys(&prefix).await {
+            self.remove_member(guild_id, &key[prefix.len()..]).await;
+        }
+    }
+
+    async fn update_user<F>(&self, key: &str, f: &F)
+    where
+        F: Fn(&mut User),
+    {
+        if let Some(mut user) = self.load::<User>(key).await {
+            f(&mut user);
+            self.store(key, &user).await;
+        }
+    }
+
+    pub(crate) async fn update(&self, event: &Event<EventExtra>) {
+        let system = match &event.extra {
+            EventExtra::GroupMessage(g) => {
+                self.insert_member(&g.guild_id, g.author.clone()).await;
+                return;
+            }
+            EventExtra::PersonMessage(p) => {
+                self.store_changed(&format!("user:{}", p.author.id), &p.author)
+                    .await;
+                return;
+            }
+            EventExtra::System(s) => s,
+        };
+        let guild_id = &event.target_id;
+        match system {
+            SystemExtra::AddedChannel(c) | SystemExtra::UpdatedChannel(c) => {
+                self.upsert_channel(c.clone()).await
+            }
+            SystemExtra::DeletedChannel(c) => self.remove_channel(&c.id).await,
+            SystemExtra::AddedRole(r) | SystemExtra::UpdatedRole(r) => {
+                self.update_roles(guild_id, |roles| {
+                    match roles.iter_mut().find(|role| role.role_id == r.role_id) {
+                        Some(role) => *role = r.clone(),
+                        None => roles.push(r.clone()),
+                    }
+                })
+                .await
+            }
+            SystemExtra::DeletedRole(r) => {
+                self.update_roles(guild_id, |roles| {
+                    roles.retain(|role| role.role_id != r.role_id)
+                })
+                .await
+            }
+            SystemExtra::JoinedGuild(m) => {
+                // only the id is known, the member is fetched on first use
+                if let Some(user) = self.user(&m.user_id).await {
+                    self.store_member(guild_id, &user).await;
+                }
+            }
+            SystemExtra::ExitedGuild(m) => self.remove_member(guild_id, &m.user_id).await,
+            SystemExtra::UpdateGuildMember(m) => {
+                let key = format!("member:{}:{}", guild_id, m.user_id);
+                self.update_user(&key, &|user| user.nickname = m.nickname.clone())
+                    .await
+            }
+            SystemExtra::UserUpdated(u) => {
+                let update = |user: &mut User| {
+                    user.username = u.username.clone();
+                    user.avatar = u.avatar.clone();
+                };
+                self.update_user(&format!("user:{}", u.user_id), &update)
+                    .await;
+                let guilds: Vec<String> = self
+                    .load(&format!("member_of:{}", u.user_id))
+                    .await
+                    .unwrap_or_default();
+                for guild_id in guilds {
+                    let key = format!("member:{}:{}", guild_id, u.user_id);
+                    self.update_user(&key, &update).await;
+                }
+            }
+            SystemExtra::UpdateGuild(g) => {
+                if let Some(mut guild) = self.guild(&g.id).await {
+                    guild.name = g.name.clone();
+                    guild.icon = g.icon.clone();
+                    guild.region = g.region.clone();
+                    guild.default_channel_id = g.default_channel_id.clone();
+                    guild.welcome_channel_id = g.welcome_channel_id.clone();
+                    self.store(&format!("guild:{}", guild.id), &guild).await;
+                }
+            }
+            SystemExtra::DeletedGuild(g) => self.remove_guild(&g.id).await,
+            SystemExtra::SelfJoinedGuild(g) | SystemExtra::SelfExitedGuild(g) => {
+                self.remove_guild(&g.guild_id).await
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Kook {
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+}
+
+#[tokio::test]
+async fn cache_test() {
+    let cache = Cache::new();
+    let guild: Guild = serde_json::from_str(
+        r#"{
+        "id": "guild",
+        "name": "guild",
+        "topic": "",
+        "master_id": "master",
+        "icon": "",
+        "notify_type": 1,
+        "region": "beijing",
+        "enable_open": false,
+        "open_id": "0",
+        "default_channel_id": "channel",
+        "welcome_channel_id": "0",
+        "roles": [
+          { "role_id": 1, "name": "admin", "color": 0, "position": 1, "hoist": 0, "mentionable": 0, "permissions": 0 }
+        ],
+        "channels": [
+          {
+            "id": "channel",
+            "name": "general",
+            "user_id": "master",
+            "guild_id": "guild",
+            "topic": "",
+            "is_category": false,
+            "parent_id": "",
+            "level": 1,
+            "slow_mode": 0,
+            "type": 1,
+            "permission_overwrites": [],
+            "permission_users": [],
+            "permission_sync": 1,
+            "has_password": false
+          }
+        ]
+      }"#,
+    )
+    .unwrap();
+    cache.insert_guild(guild).await;
+    assert_eq!(cache.channel("channel").await.unwrap().name, "general");
+
+    let event = crate::test::system_event(
+        "guild",
+        serde_json::json!({
+            "type": "updated_channel",
+            "body": {
+                "id": "channel",
+                "name": "renamed",
+                "user_id": "master",
+                "guild_id": "guild",
+                "topic": "",
+                "is_category": 0,
+                "parent_id": "",
+                "level": 1,
+                "slow_mode": 0,
+                "type": 1,
+                "permission_overwrites": [],
+                "permission_users": [],
+                "permission_sync": 1,
+                "has_password": false
+            }
+        }),
+    );
+    cache.update(&event).await;
+    assert_eq!(cache.channel("channel").await.unwrap().name, "renamed");
+    assert_eq!(
+        cache.guild("guild").await.unwrap().channels[0].name,
+        "renamed"
+    );
+
+    let user: User = serde_json::from_value(crate::test::user("user")).unwrap();
+    cache.insert_member("guild", user.clone()).await;
+    cache.insert_member("other", user).await;
+    let mut event = event;
+    event.extra = EventExtra::System(SystemExtra::UserUpdated(UserUpdated {
+        user_id: "user".to_owned(),
+        username: "renamed".to_owned(),
+        avatar: "avatar".to_owned(),
+    }));
+    cache.update(&event).await;
+    for guild_id in ["guild", "other"] {
+        let member = cache.member(guild_id, "user").await.unwrap();
+        assert_eq!(member.username, "renamed");
+    }
+
+    event.extra = EventExtra::System(SystemExtra::DeletedRole(
+        cache.role("guild", 1).await.unwrap(),
+    ));
+    cache.update(&event).await;
+    assert!(cache.role("guild", 1).await.is_none());
+
+    event.extra = EventExtra::System(SystemExtra::SelfExitedGuild(SelfGuild {
+        guild_id: "guild".to_owned(),
+    }));
+    cache.update(&event).await;
+    assert!(cache.guild("guild").await.is_none());
+    assert!(cache.channel("channel").await.is_none());
+    assert!(cache.member("guild", "user").await.is_none());
+    let guilds: Vec<String> = cache.load("member_of:user").await.unwrap();
+    assert_eq!(guilds, vec!["other"]);
+}
+
+#[tokio::test]
+async fn cache_order_test() {
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Yields on reads, so unordered updates of one guild interleave.
+    #[derive(Default)]
+    struct Slow(MemoryBackend);
+
+    #[async_trait]
+    impl CacheBackend for Slow {
+        async fn get(&self, key: &str) -> Option<Vec<u8>> {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.0.get(key).await
+        }
+        async fn set(&self, key: &str, value: Vec<u8>) {
+            self.0.set(key, value).await
+        }
+        async fn remove(&self, key: &str) {
+            self.0.remove(key).await
+        }
+        async fn keys(&self, prefix: &str) -> Vec<String> {
+            self.0.keys(prefix).await
+        }
+    }
+
+    let cache = Cache::with_backend(Slow::default());
+    let guild: Guild = serde_json::from_value(serde_json::json!({
+        "id": "guild",
+        "name": "guild",
+        "topic": "",
+        "master_id": "master",
+        "icon": "",
+        "notify_type": 1,
+        "region": "beijing",
+        "enable_open": false,
+        "open_id": "0",
+        "default_channel_id": "channel",
+        "welcome_channel_id": "0",
+        "roles": [],
+        "channels": []
+    }))
+    .unwrap();
+    cache.insert_guild(guild).await;
+    let kook = Kook::new_from_config(Config::default(), ())
+        .with_cache(cache)
+        .arc();
+    for role_id in 1..=2 {
+        let event = crate::test::system_event(
+            "guild",
+            serde_json::json!({
+                "type": "added_role",
+                "body": {
+                    "role_id": role_id, "name": "role", "color": 0, "position": 1,
+                    "hoist": 0, "mentionable": 0, "permissions": 0
+                }
+            }),
+        );
+        kook.submit(event);
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let cache = kook.cache().unwrap();
+    assert!(cache.role("guild", 1).await.is_some());
+    assert!(cache.role("guild", 2).await.is_some());
+}
@@ -0,0 +1,419 @@
+use std::{sync::Arc, time::Duration};
+
+use super::*;
+use crate::prelude::Timestamp;
+
+/// `plain-text` or `kmarkdown`, a `&str` converts to kmarkdown.
+#[derive(Debug, Clone)]
+pub struct Text(CardItem);
+
+impl Text {
+    pub fn plain(content: impl Into<String>) -> Self {
+        Self(CardItem::PlainText {
+            content: content.into(),
+            emoji: None,
+        })
+    }
+
+    pub fn kmarkdown(content: impl Into<String>) -> Self {
+        Self(CardItem::Kmarkdown {
+            content: content.into(),
+        })
+    }
+}
+
+impl From<&str> for Text {
+    fn from(content: &str) -> Self {
+        Self::kmarkdown(content)
+    }
+}
+
+impl From<String> for Text {
+    fn from(content: String) -> Self {
+        Self::kmarkdown(content)
+    }
+}
+
+/// Text in `cols` columns.
+#[derive(Debug, Clone)]
+pub struct Paragraph {
+    cols: u8,
+    fields: Vec<CardItem>,
+}
+
+impl Paragraph {
+    pub fn new(cols: u8) -> Self {
+        Self {
+            cols,
+            fields: vec![],
+        }
+    }
+
+    pub fn field(mut self, field: impl Into<Text>) -> Self {
+        self.fields.push(field.into().0);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Image {
+    src: String,
+    alt: Option<String>,
+    size: Option<Size>,
+    circle: Option<bool>,
+}
+
+impl Image {
+    pub fn new(src: impl Into<String>) -> Self {
+        Self {
+            src: src.into(),
+            alt: None,
+            size: None,
+            circle: None,
+        }
+    }
+
+    pub fn alt(mut self, alt: impl Into<String>) -> Self {
+        self.alt = Some(alt.into());
+        self
+    }
+
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn circle(mut self, circle: bool) -> Self {
+        self.circle = Some(circle);
+        self
+    }
+}
+
+impl From<Image> for CardItem {
+    fn from(image: Image) -> Self {
+        CardItem::Image {
+            src: image.src,
+            alt: image.alt,
+            size: image.size,
+            circle: image.circle,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Button {
+    theme: Option<Theme>,
+    value: String,
+    click: ButtonClick,
+    text: Text,
+}
+
+impl Button {
+    /// A button sending `value` back in a `MessageBtnClick` event.
+    pub fn new(text: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            theme: None,
+            value: value.into(),
+            click: ButtonClick::ReturnVal,
+            text: Text::plain(text),
+        }
+    }
+
+    /// A button opening `url`.
+    pub fn link(text: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            click: ButtonClick::Link,
+            ..Self::new(text, url)
+        }
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Run `handler` on clicks, replacing the value with a generated one.
+    /// The callback is kept for `CLICK_TTL`.
+    pub fn on_click<H>(self, handler: H) -> Self
+    where
+        H: ClickHandler + 'static,
+    {
+        self.on_click_for(handler, CLICK_TTL)
+    }
+
+    pub fn on_click_for<H>(mut self, handler: H, ttl: Duration) -> Self
+    where
+        H: ClickHandler + 'static,
+    {
+        self.click = ButtonClick::ReturnVal;
+        self.value = register_click(Arc::new(handler), ttl);
+        self
+    }
+
+    /// The value sent back on clicks.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Use kmarkdown for the label.
+    pub fn kmarkdown(mut self) -> Self {
+        if let CardItem::PlainText { content, .. } = self.text.0 {
+            self.text = Text::kmarkdown(content);
+        }
+        self
+    }
+}
+
+impl From<Button> for CardItem {
+    fn from(button: Button) -> Self {
+        CardItem::Button {
+            theme: button.theme,
+            value: Some(button.value),
+            click: Some(button.click),
+            text: Box::new(button.text.0),
+        }
+    }
+}
+
+macro_rules! element {
+    ($(#[$meta: meta])* $name: ident: $($from: ty),*) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name(CardItem);
+
+        $(
+            impl From<$from> for $name {
+                fn from(item: $from) -> Self {
+                    Self(item.into())
+                }
+            }
+        )*
+    };
+}
+
+impl From<Text> for CardItem {
+    fn from(text: Text) -> Self {
+        text.0
+    }
+}
+
+impl From<Paragraph> for CardItem {
+    fn from(paragraph: Paragraph) -> Self {
+        CardItem::Paragraph {
+            cols: paragraph.cols,
+            fields: paragraph.fields,
+        }
+    }
+}
+
+element!(
+    /// Text of a `section`, text or a paragraph.
+    SectionText: Text, Paragraph
+);
+element!(
+    /// Accessory of a `section`, an image or a button.
+    Accessory: Image, Button
+);
+element!(
+    /// Element of a `context`, text or an image.
+    ContextElement: Text, Image
+);
+
+impl From<&str> for SectionText {
+    fn from(content: &str) -> Self {
+        Self(Text::from(content).0)
+    }
+}
+
+impl From<String> for SectionText {
+    fn from(content: String) -> Self {
+        Self(Text::from(content).0)
+    }
+}
+
+impl From<&str> for ContextElement {
+    fn from(content: &str) -> Self {
+        Self(Text::from(content).0)
+    }
+}
+
+impl Card {
+    /// ### Example:
+    /// ```ignore
+    /// let card = Card::new()
+    ///     .theme(Theme::Info)
+    ///     .header("Poll")
+    ///     .section_with("Which one?", Image::new(url).size(Size::Sm))
+    ///     .divider()
+    ///     .action_group([Button::new("A", "a"), Button::new("B", "b")]);
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Color of the side bar, e.g. `#aaaaaa`.
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn module(mut self, module: CardModule) -> Self {
+        self.modules.push(module);
+        self
+    }
+
+    pub fn header(self, text: impl Into<String>) -> Self {
+        self.module(CardModule::Header {
+            text: Text::plain(text).0,
+        })
+    }
+
+    pub fn section(self, text: impl Into<SectionText>) -> Self {
+        self.module(CardModule::Section {
+            mode: None,
+            text: text.into().0,
+            accessory: None,
+        })
+    }
+
+    /// A section with `accessory` on the right.
+    pub fn section_with(
+        self,
+        text: impl Into<SectionText>,
+        accessory: impl Into<Accessory>,
+    ) -> Self {
+        self.module(CardModule::Section {
+            mode: Some(SectionMode::Right),
+            text: text.into().0,
+            accessory: Some(accessory.into().0),
+        })
+    }
+
+    /// A section with `image` on the left, buttons can only go on the right.
+    pub fn section_left(self, text: impl Into<SectionText>, image: Image) -> Self {
+        self.module(CardModule::Section {
+            mode: Some(SectionMode::Left),
+            text: text.into().0,
+            accessory: Some(image.into()),
+        })
+    }
+
+    pub fn image_group(self, images: impl IntoIterator<Item = Image>) -> Self {
+        self.module(CardModule::ImageGroup {
+            elements: images.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    pub fn container(self, images: impl IntoIterator<Item = Image>) -> Self {
+        self.module(CardModule::Container {
+            elements: images.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    pub fn action_group(self, buttons: impl IntoIterator<Item = Button>) -> Self {
+        self.module(CardModule::ActionGroup {
+            elements: buttons.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    pub fn context<E>(self, elements: impl IntoIterator<Item = E>) -> Self
+    where
+        E: Into<ContextElement>,
+    {
+        self.module(CardModule::Context {
+            elements: elements.into_iter().map(|e| e.into().0).collect(),
+        })
+    }
+
+    pub fn divider(self) -> Self {
+        self.module(CardModule::Divider)
+    }
+
+    pub fn file(self, src: impl Into<String>, title: impl Into<String>) -> Self {
+        self.module(CardModule::File {
+            src: src.into(),
+            title: Some(title.into()),
+            cover: None,
+        })
+    }
+
+    pub fn audio(
+        self,
+        src: impl Into<String>,
+        title: impl Into<String>,
+        cover: impl Into<String>,
+    ) -> Self {
+        self.module(CardModule::Audio {
+            src: src.into(),
+            title: Some(title.into()),
+            cover: Some(cover.into()),
+        })
+    }
+
+    pub fn video(self, src: impl Into<String>, title: impl Into<String>) -> Self {
+        self.module(CardModule::Video {
+            src: src.into(),
+            title: Some(title.into()),
+            cover: None,
+        })
+    }
+
+    /// Count down from now to `end_time`.
+    pub fn countdown(self, end_time: Timestamp, mode: CountdownMode) -> Self {
+        self.module(CardModule::Countdown {
+            mode,
+            end_time: end_time.as_millis() as u64,
+            start_time: Some(Timestamp::now().as_millis() as u64),
+        })
+    }
+
+    pub fn invite(self, code: impl Into<String>) -> Self {
+        self.module(CardModule::Invite { code: code.into() })
+    }
+}
+
+#[test]
+fn builder_test() {
+    let cards = vec![Card::new()
+        .theme(Theme::Info)
+        .size(Size::Lg)
+        .header("Title")
+        .section("**bold**")
+        .section_with(
+            Paragraph::new(2).field("a").field(Text::plain("b")),
+            Button::new("Click", "clicked").theme(Theme::Danger),
+        )
+        .image_group([Image::new("https://img").alt("img")])
+        .context([
+            ContextElement::from("tip"),
+            Image::new("https://icon").into(),
+        ])
+        .divider()
+        .action_group([Button::link("Open", "https://kookapp.cn")])];
+    let json = serde_json::to_value(&cards).unwrap();
+    assert_eq!(json[0]["type"], "card");
+    assert_eq!(json[0]["theme"], "info");
+    assert_eq!(json[0]["size"], "lg");
+    let modules = &json[0]["modules"];
+    assert_eq!(modules[0]["text"]["type"], "plain-text");
+    assert_eq!(modules[1]["text"]["type"], "kmarkdown");
+    assert_eq!(modules[2]["text"]["fields"][1]["type"], "plain-text");
+    assert_eq!(modules[2]["mode"], "right");
+    assert_eq!(modules[2]["accessory"]["theme"], "danger");
+    assert_eq!(modules[2]["accessory"]["text"]["content"], "Click");
+    assert_eq!(modules[3]["elements"][0]["alt"], "img");
+    assert_eq!(modules[4]["elements"][1]["type"], "image");
+    assert!(modules[4]["elements"][1].get("circle").is_none());
+    assert_eq!(modules[6]["elements"][0]["click"], "link");
+    assert!(cards_decode(&cards_encode(&cards)).is_ok());
+}
@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::prelude::*;
+
+/// How long a callback set with `Button::on_click` is kept.
+pub const CLICK_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Called when a button built with `Button::on_click` is clicked,
+/// implemented for `Fn(Context, MessageBtnClick) -> impl Future`.
+#[async_trait]
+pub trait ClickHandler: Send + Sync {
+    async fn on_click(&self, ctx: &Context, click: &MessageBtnClick) -> HandlerResult;
+}
+
+#[async_trait]
+impl<F, Fut> ClickHandler for F
+where
+    F: Fn(Context, MessageBtnClick) -> Fut + Send + Sync,
+    Fut: Future<Output = HandlerResult> + Send,
+{
+    async fn on_click(&self, ctx: &Context, click: &MessageBtnClick) -> HandlerResult {
+        self(ctx.clone(), click.clone()).await
+    }
+}
+
+struct Callback {
+    handler: Arc<dyn ClickHandler>,
+    expires: Instant,
+}
+
+/// Process wide so buttons can be built before a `Kook` is around, the
+/// generated values keep callbacks apart.
+static CALLBACKS: OnceLock<DashMap<String, Callback>> = OnceLock::new();
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Size of `CALLBACKS` at which expired callbacks are swept next.
+static SWEEP_AT: AtomicUsize = AtomicUsize::new(MIN_SWEEP);
+const MIN_SWEEP: usize = 64;
+
+/// Store `handler` under a new button value, dropping expired callbacks
+/// once the map has doubled since the last sweep.
+pub(crate) fn register_click(handler: Arc<dyn ClickHandler>, ttl: Duration) -> String {
+    let value = format!(
+        "kook-click:{:x}:{:x}",
+        Timestamp::now().as_millis(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    );
+    let callbacks = CALLBACKS.get_or_init(DashMap::default);
+    let now = Instant::now();
+    if callbacks.len() >= SWEEP_AT.load(Ordering::Relaxed) {
+        callbacks.retain(|_, c| c.expires > now);
+        SWEEP_AT.store((callbacks.len() * 2).max(MIN_SWEEP), Ordering::Relaxed);
+    }
+    callbacks.insert(
+        value.clone(),
+        Callback {
+            handler,
+            expires: now + ttl,
+        },
+    );
+    value
+}
+
+/// Forget the callback of a button before it expires, e.g. once a menu is closed.
+pub fn remove_click_handler(value: &str) {
+    if let Some(callbacks) = CALLBACKS.get() {
+        callbacks.remove(value);
+    }
+}
+
+/// The live callback for a `MessageBtnClick` event.
+fn click_handler(event: &Event<EventExtra>) -> Option<(Arc<dyn ClickHandler>, &MessageBtnClick)> {
+    let click = match &event.extra {
+        EventExtra::System(SystemExtra::MessageBtnClick(click)) => click,
+        _ => return None,
+    };
+    let callbacks = CALLBACKS.get()?;
+    let callback = callbacks.get(&click.value)?;
+    if callback.expires <= Instant::now() {
+        drop(callback);
+        callbacks.remove(&click.value);
+        return None;
+    }
+    Some((callback.handler.clone(), click))
+}
+
+/// Routes clicks to their callbacks, registered with the `Router` of every
+/// `Kook` alongside the user's handlers, which all run concurrently behind
+/// the middlewares.
+pub(crate) struct Clicks;
+
+#[async_trait]
+impl EventHandler for Clicks {
+    async fn _handle(&self, ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+        match click_handler(&event) {
+            Some((handler, click)) => handler.on_click(ctx, click).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn click_test() {
+    use super::{Button, Card};
+    use std::sync::Mutex;
+
+    let clicked = Arc::new(Mutex::new(vec![]));
+    let record = |clicked: &Arc<Mutex<Vec<String>>>| {
+        let recorded = clicked.clone();
+        move |_ctx: Context, click: MessageBtnClick| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().unwrap().push(click.user_id);
+                Ok(())
+            }
+        }
+    };
+    let live = Button::new("Yes", "").on_click(record(&clicked));
+    let stale = Button::new("No", "").on_click_for(
+        |_ctx: Context, _click: MessageBtnClick| async { panic!("expired") },
+        Duration::ZERO,
+    );
+    let blocked = Button::new("Maybe", "").on_click(record(&clicked));
+    let cards = vec![Card::new().action_group([live, stale, blocked])];
+    let json = serde_json::to_value(&cards).unwrap();
+    let values: Vec<String> = (0..3)
+        .map(|i| {
+            json[0]["modules"][0]["elements"][i]["value"]
+                .as_str()
+                .unwrap()
+                .to_owned()
+        })
+        .collect();
+    assert_ne!(values[0], values[1]);
+
+    let kook = Kook::new_from_config(Config::default(), ())
+        .with_middleware(Blocklist(["blocked".to_owned()].into()))
+        .arc();
+    for (value, user) in values.iter().zip(["user", "user", "blocked"]) {
+        let event = crate::test::button_click(value, user);
+        kook.dispatch(event, None).await;
+    }
+    assert_eq!(*clicked.lock().unwrap(), vec!["user"]);
+
+    remove_click_handler(&values[0]);
+    assert!(CALLBACKS.get().unwrap().get(&values[0]).is_none());
+}
@@ -0,0 +1,240 @@
+mod builder;
+mod click;
+mod render;
+mod template;
+mod validate;
+
+pub use builder::*;
+pub(crate) use click::{register_click, Clicks};
+pub use click::{remove_click_handler, ClickHandler, CLICK_TTL};
+pub use render::*;
+pub use template::*;
+pub use validate::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::KookError;
+
+pub fn cards_decode(s: &str) -> Result<Cards, KookError> {
+    serde_json::from_str(s).map_err(KookError::SerdeJsonError)
+}
+
+pub fn cards_encode(cards: &Cards) -> String {
+    serde_json::to_string(cards).unwrap()
+}
+
+pub type Cards = Vec<Card>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Primary,
+    Success,
+    Danger,
+    Warning,
+    Info,
+    Secondary,
+    None,
+    Invisible,
+}
+
+/// Size of a card or an image, KOOK only documents `sm` and `lg`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Size {
+    Xs,
+    Sm,
+    Md,
+    #[default]
+    Lg,
+}
+
+/// Side of a `section` the accessory is placed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SectionMode {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CountdownMode {
+    Day,
+    Hour,
+    /// Needs `start_time`.
+    Second,
+}
+
+/// What clicking a button does, nothing when absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ButtonClick {
+    /// Open `value` as an url.
+    Link,
+    /// Send `value` back in a `MessageBtnClick` event.
+    ReturnVal,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "card")]
+pub struct Card {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+    /// Color of the side bar, e.g. `#aaaaaa`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<Size>,
+    pub modules: Vec<CardModule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum CardModule {
+    Header {
+        // plain-text
+        text: CardItem,
+    },
+    Section {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mode: Option<SectionMode>,
+        // plain-text|kmarkdown|paragraph
+        text: CardItem,
+        // image|button
+        #[serde(skip_serializing_if = "Option::is_none")]
+        accessory: Option<CardItem>,
+    },
+    ImageGroup {
+        // image
+        elements: Vec<CardItem>,
+    },
+    Container {
+        // image
+        elements: Vec<CardItem>,
+    },
+    ActionGroup {
+        // button
+        elements: Vec<CardItem>,
+    },
+    Context {
+        // plain-text|kmarkdown|image
+        elements: Vec<CardItem>,
+    },
+    Divider,
+    File {
+        src: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cover: Option<String>,
+    },
+    Audio {
+        src: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cover: Option<String>,
+    },
+    Video {
+        src: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cover: Option<String>,
+    },
+    Countdown {
+        mode: CountdownMode,
+        #[serde(rename = "endTime")]
+        end_time: u64,
+        #[serde(rename = "startTime", skip_serializing_if = "Option::is_none")]
+        start_time: Option<u64>,
+    },
+    Invite {
+        code: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum CardItem {
+    PlainText {
+        content: String,
+        /// Convert emoji shortcodes, KOOK defaults to `true`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        emoji: Option<bool>,
+    },
+    Kmarkdown {
+        content: String,
+    },
+    Image {
+        src: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        alt: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        size: Option<Size>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        circle: Option<bool>,
+    },
+    Button {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        theme: Option<Theme>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        click: Option<ButtonClick>,
+        // plain-text|kmarkdown
+        text: Box<CardItem>,
+    },
+    Paragraph {
+        cols: u8,
+        // plain-text|kmarkdown
+        fields: Vec<CardItem>,
+    },
+}
+
+#[test]
+fn card_round_trip_test() {
+    // examples from the KOOK card message docs
+    let examples = [
+        r#"[{"type":"card","theme":"secondary","size":"lg","modules":[
+            {"type":"header","text":{"type":"plain-text","content":"今日推荐"}},
+            {"type":"section","text":{"type":"kmarkdown","content":"**标题**"},"mode":"right",
+             "accessory":{"type":"image","src":"https://img.kaiheila.cn/assets/2021-01/7kr4FkWpLV0ku0ku.jpeg","size":"lg"}},
+            {"type":"section","text":{"type":"plain-text","content":"您是否认为\"开黑啦\"是最好的语音软件？"},"mode":"right",
+             "accessory":{"type":"button","theme":"primary","text":{"type":"plain-text","content":"完全同意"}}},
+            {"type":"section","text":{"type":"paragraph","cols":3,"fields":[
+                {"type":"kmarkdown","content":"**昵称**\n怪才君"},
+                {"type":"kmarkdown","content":"**服务器**\n活动中心"},
+                {"type":"kmarkdown","content":"**在线时间**\n9:00-21:00"}]}},
+            {"type":"divider"},
+            {"type":"image-group","elements":[
+                {"type":"image","src":"https://img.kaiheila.cn/assets/2021-01/pWsmcLsPJq08c08c.jpeg"},
+                {"type":"image","src":"https://img.kaiheila.cn/assets/2021-01/YIfHfnvxaV0dw0dw.jpg"}]},
+            {"type":"container","elements":[
+                {"type":"image","src":"https://img.kaiheila.cn/assets/2021-01/pWsmcLsPJq08c08c.jpeg"}]},
+            {"type":"action-group","elements":[
+                {"type":"button","theme":"primary","value":"ok","click":"return-val","text":{"type":"plain-text","content":"确定"}},
+                {"type":"button","theme":"danger","value":"https://www.kookapp.cn","click":"link","text":{"type":"plain-text","content":"取消"}}]},
+            {"type":"context","elements":[
+                {"type":"plain-text","content":"开黑啦气氛组"},
+                {"type":"image","src":"https://img.kaiheila.cn/assets/2021-01/FckX3MDe6S02i020.png","alt":"","circle":true}]}
+        ]}]"#,
+        r##"[{"type":"card","theme":"info","color":"#aaaaaa","modules":[
+            {"type":"file","title":"开黑啦介绍.pdf","src":"https://img.kaiheila.cn/attachments/2021-01/21/600972b5d0d31.pdf"},
+            {"type":"audio","title":"命运交响曲","src":"https://img.kaiheila.cn/attachments/2021-01/21/600975671b9ab.mp3",
+             "cover":"https://img.kaiheila.cn/assets/2021-01/rcdqa8fAOO0hs0mc.jpg"},
+            {"type":"video","title":"12FPS","src":"https://img.kaiheila.cn/attachments/2021-01/20/6008127e8c8de.mp4"},
+            {"type":"countdown","mode":"day","endTime":1608883200000},
+            {"type":"countdown","mode":"second","startTime":1608819600000,"endTime":1608883200000},
+            {"type":"invite","code":"ZbXKJm"}
+        ]}]"##,
+    ];
+    for example in examples {
+        let json: serde_json::Value = serde_json::from_str(example).unwrap();
+        let cards = cards_decode(example).unwrap();
+        assert!(cards.validate().is_ok());
+        assert_eq!(serde_json::to_value(&cards).unwrap(), json);
+    }
+}
@@ -0,0 +1,161 @@
+use super::*;
+use crate::{kmd_escape, kmd_from_str};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    KMarkdown,
+    Plain,
+}
+
+/// Render `cards` as KMarkdown, for clients that can't show cards.
+pub fn cards_to_kmarkdown(cards: &Cards) -> String {
+    render(cards, Style::KMarkdown)
+}
+
+/// Render `cards` as plain text, for logs and snapshot tests.
+pub fn cards_to_text(cards: &Cards) -> String {
+    render(cards, Style::Plain)
+}
+
+fn render(cards: &Cards, style: Style) -> String {
+    cards
+        .iter()
+        .map(|card| {
+            card.modules
+                .iter()
+                .map(|m| module(m, style))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn module(module: &CardModule, style: Style) -> String {
+    let join = |items: &[CardItem], sep: &str| {
+        items
+            .iter()
+            .map(|i| item(i, style))
+            .collect::<Vec<_>>()
+            .join(sep)
+    };
+    match module {
+        CardModule::Header { text } => match style {
+            Style::KMarkdown => format!("**{}**", item(text, style)),
+            Style::Plain => item(text, style),
+        },
+        CardModule::Section {
+            text, accessory, ..
+        } => match accessory {
+            Some(accessory) => format!("{}\n{}", item(text, style), item(accessory, style)),
+            None => item(text, style),
+        },
+        CardModule::ImageGroup { elements } | CardModule::Container { elements } => {
+            join(elements, "\n")
+        }
+        CardModule::ActionGroup { elements } => join(elements, " "),
+        CardModule::Context { elements } => join(elements, " "),
+        CardModule::Divider => "---".to_owned(),
+        CardModule::File { src, title, .. }
+        | CardModule::Audio { src, title, .. }
+        | CardModule::Video { src, title, .. } => link(title.as_deref().unwrap_or(src), src, style),
+        CardModule::Countdown { end_time, .. } => format!("Countdown to {}", end_time),
+        CardModule::Invite { code } => format!(
+            "Invite: {}",
+            link(code, &format!("https://kook.top/{}", code), style)
+        ),
+    }
+}
+
+fn item(item: &CardItem, style: Style) -> String {
+    match (item, style) {
+        (CardItem::PlainText { content, .. }, Style::KMarkdown) => kmd_escape(content),
+        (CardItem::PlainText { content, .. }, Style::Plain) => content.clone(),
+        (CardItem::Kmarkdown { content }, Style::KMarkdown) => content.clone(),
+        (CardItem::Kmarkdown { content }, Style::Plain) => kmd_from_str(content)
+            .iter()
+            .map(|kmd| kmd.plain_text())
+            .collect(),
+        (CardItem::Image { src, alt, .. }, _) => link(
+            alt.as_deref().filter(|a| !a.is_empty()).unwrap_or("image"),
+            src,
+            style,
+        ),
+        (
+            CardItem::Button {
+                value, click, text, ..
+            },
+            _,
+        ) => {
+            let label = self::item(text, Style::Plain);
+            match (click, value) {
+                (Some(ButtonClick::Link), Some(url)) => link(&label, url, style),
+                _ if style == Style::KMarkdown => kmd_escape(&format!("[{}]", label)),
+                _ => format!("[{}]", label),
+            }
+        }
+        // one row per `cols` fields
+        (CardItem::Paragraph { cols, fields }, _) => fields
+            .chunks((*cols).max(1) as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|f| self::item(f, style).replace('\n', " "))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn link(text: &str, url: &str, style: Style) -> String {
+    match style {
+        Style::KMarkdown => format!("[{}]({})", kmd_escape(text), url),
+        Style::Plain => format!("{} ({})", text, url),
+    }
+}
+
+#[test]
+fn render_test() {
+    let cards = vec![
+        Card::new()
+            .header("Weekly *stats*")
+            .section(
+                Paragraph::new(2)
+                    .field("**Name**\nAlice")
+                    .field("**Score**\n42")
+                    .field("Bob")
+                    .field("7"),
+            )
+            .section_with("Avatar", Image::new("https://img/a.png"))
+            .action_group([
+                Button::new("Refresh", "refresh"),
+                Button::link("Site", "https://kookapp.cn"),
+            ]),
+        Card::new().divider().invite("abc"),
+    ];
+    assert_eq!(
+        cards_to_kmarkdown(&cards),
+        "**Weekly \\*stats\\***
+**Name** Alice | **Score** 42
+Bob | 7
+Avatar
+[image](https://img/a.png)
+\\[Refresh\\] [Site](https://kookapp.cn)
+
+---
+Invite: [abc](https://kook.top/abc)"
+    );
+    assert_eq!(
+        cards_to_text(&cards),
+        "Weekly *stats*
+Name Alice | Score 42
+Bob | 7
+Avatar
+image (https://img/a.png)
+[Refresh] Site (https://kookapp.cn)
+
+---
+Invite: abc (https://kook.top/abc)"
+    );
+}
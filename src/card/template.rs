@@ -0,0 +1,250 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use super::*;
+use crate::kmd_escape;
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("serde_json error:{0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("`{0}` is missing from the context")]
+    Missing(String),
+    #[error("`{0}` is not an array")]
+    NotArray(String),
+    #[error("unclosed placeholder in `{0}`")]
+    Unclosed(String),
+    #[error("a loop needs `$each` naming an array and `$items`")]
+    BadLoop,
+}
+
+/// Cards with placeholders filled from a serializable context on render.
+///
+/// Any string may hold `{{ path }}` placeholders, a dotted path into the
+/// context. Values inserted into `kmarkdown` content are escaped, use
+/// `{{{ path }}}` to insert KMarkdown as is. A string that is a single
+/// placeholder takes the value as is, so numbers like `cols` work.
+///
+/// An object with `$each` in an array repeats `$items` for every element,
+/// bound to `$as` (`item` by default) along with `@index` and `@number`:
+/// ```json
+/// [{"type": "card", "modules": [
+///   {"type": "header", "text": {"type": "plain-text", "content": "{{ title }}"}},
+///   {"$each": "players", "$as": "p", "$items": [
+///     {"type": "section", "text": {"type": "kmarkdown", "content": "{{ @number }}. **{{ p.name }}** {{ p.score }}"}}
+///   ]}
+/// ]}]
+/// ```
+#[derive(Debug, Clone)]
+pub struct CardTemplate(Value);
+
+impl CardTemplate {
+    pub fn parse(s: &str) -> Result<Self, TemplateError> {
+        Ok(Self(serde_json::from_str(s)?))
+    }
+
+    /// A template from builder output, placeholders go in its strings.
+    pub fn from_cards(cards: &Cards) -> Self {
+        Self(serde_json::to_value(cards).unwrap())
+    }
+
+    pub fn render<C: Serialize>(&self, context: &C) -> Result<Cards, TemplateError> {
+        let mut scope = Scope {
+            root: serde_json::to_value(context)?,
+            vars: vec![],
+        };
+        Ok(serde_json::from_value(fill(&self.0, &mut scope, false)?)?)
+    }
+}
+
+struct Scope {
+    root: Value,
+    vars: Vec<(String, Value)>,
+}
+
+impl Scope {
+    fn lookup(&self, path: &str) -> Result<&Value, TemplateError> {
+        let missing = || TemplateError::Missing(path.to_owned());
+        let mut segments = path.split('.');
+        let first = segments.next().unwrap_or_default();
+        let mut value = match self.vars.iter().rev().find(|(name, _)| name == first) {
+            Some((_, value)) => value,
+            None => self.root.get(first).ok_or_else(missing)?,
+        };
+        for segment in segments {
+            value = match value {
+                Value::Array(a) => segment.parse().ok().and_then(|i: usize| a.get(i)),
+                _ => value.get(segment),
+            }
+            .ok_or_else(missing)?;
+        }
+        Ok(value)
+    }
+}
+
+fn fill(value: &Value, scope: &mut Scope, kmarkdown: bool) -> Result<Value, TemplateError> {
+    Ok(match value {
+        Value::String(s) => fill_str(s, scope, kmarkdown)?,
+        Value::Array(items) => {
+            let mut out = vec![];
+            fill_items(items, scope, &mut out)?;
+            Value::Array(out)
+        }
+        Value::Object(object) => {
+            let kmarkdown = object.get("type").and_then(Value::as_str) == Some("kmarkdown");
+            let mut out = Map::new();
+            for (key, value) in object {
+                out.insert(
+                    key.clone(),
+                    fill(value, scope, kmarkdown && key == "content")?,
+                );
+            }
+            Value::Object(out)
+        }
+        _ => value.clone(),
+    })
+}
+
+fn fill_items(
+    items: &[Value],
+    scope: &mut Scope,
+    out: &mut Vec<Value>,
+) -> Result<(), TemplateError> {
+    for item in items {
+        let each = match item.get("$each") {
+            Some(each) => each,
+            None => {
+                out.push(fill(item, scope, false)?);
+                continue;
+            }
+        };
+        let (path, templates) = match (each.as_str(), item.get("$items")) {
+            (Some(path), Some(Value::Array(templates))) => (path, templates),
+            _ => return Err(TemplateError::BadLoop),
+        };
+        let name = item.get("$as").and_then(Value::as_str).unwrap_or("item");
+        let elements = match scope.lookup(path)? {
+            Value::Array(elements) => elements.clone(),
+            _ => return Err(TemplateError::NotArray(path.to_owned())),
+        };
+        for (index, element) in elements.into_iter().enumerate() {
+            scope.vars.push((name.to_owned(), element));
+            scope.vars.push(("@index".to_owned(), index.into()));
+            scope.vars.push(("@number".to_owned(), (index + 1).into()));
+            let filled = fill_items(templates, scope, out);
+            scope.vars.truncate(scope.vars.len() - 3);
+            filled?;
+        }
+    }
+    Ok(())
+}
+
+fn fill_str(s: &str, scope: &Scope, kmarkdown: bool) -> Result<Value, TemplateError> {
+    let text = |value: &Value, raw: bool| {
+        let s = match value {
+            Value::String(s) => s.clone(),
+            Value::Null => String::default(),
+            _ => value.to_string(),
+        };
+        if kmarkdown && !raw {
+            kmd_escape(&s)
+        } else {
+            s
+        }
+    };
+    // a lone placeholder keeps the type of the value
+    if let Some(path) = s
+        .strip_prefix("{{")
+        .and_then(|s| s.strip_suffix("}}"))
+        .filter(|p| !p.contains('{') && !p.contains('}'))
+    {
+        let value = scope.lookup(path.trim())?;
+        return Ok(match value {
+            Value::String(_) => Value::String(text(value, false)),
+            _ => value.clone(),
+        });
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let raw = rest[start..].starts_with("{{{");
+        let (open, close) = if raw { ("{{{", "}}}") } else { ("{{", "}}") };
+        let after = &rest[start + open.len()..];
+        let end = after
+            .find(close)
+            .ok_or_else(|| TemplateError::Unclosed(s.to_owned()))?;
+        out.push_str(&text(scope.lookup(after[..end].trim())?, raw));
+        rest = &after[end + close.len()..];
+    }
+    out.push_str(rest);
+    Ok(Value::String(out))
+}
+
+#[test]
+fn template_test() {
+    let template = CardTemplate::parse(
+        r#"[{"type": "card", "modules": [
+            {"type": "header", "text": {"type": "plain-text", "content": "{{ title }}"}},
+            {"type": "section", "text": {"type": "paragraph", "cols": "{{ cols }}", "fields": [
+                {"$each": "players", "$as": "p", "$items": [
+                    {"type": "kmarkdown", "content": "{{ @number }}. **{{ p.name }}**"},
+                    {"type": "kmarkdown", "content": "{{{ p.badge }}} {{ p.score }}"}
+                ]}
+            ]}},
+            {"type": "context", "elements": [
+                {"type": "image", "src": "{{ players.0.avatar }}"}
+            ]}
+        ]}]"#,
+    )
+    .unwrap();
+    let cards = template
+        .render(&serde_json::json!({
+            "title": "Top *players*",
+            "cols": 2,
+            "players": [
+                { "name": "a*b", "score": 10, "badge": "**gold**", "avatar": "https://img/a" },
+                { "name": "c", "score": 5, "badge": "", "avatar": "https://img/c" }
+            ]
+        }))
+        .unwrap();
+    let json = serde_json::to_value(&cards).unwrap();
+    let modules = &json[0]["modules"];
+    assert_eq!(modules[0]["text"]["content"], "Top *players*");
+    assert_eq!(modules[1]["text"]["cols"], 2);
+    let fields = &modules[1]["text"]["fields"];
+    assert_eq!(fields[0]["content"], "1. **a\\*b**");
+    assert_eq!(fields[1]["content"], "**gold** 10");
+    assert_eq!(fields[2]["content"], "2. **c**");
+    assert_eq!(fields.as_array().unwrap().len(), 4);
+    assert_eq!(modules[2]["elements"][0]["src"], "https://img/a");
+
+    let greeting = CardTemplate::from_cards(&vec![Card::new().section("Hi {{ name }}")]);
+    let cards = greeting
+        .render(&serde_json::json!({ "name": "(x)" }))
+        .unwrap();
+    assert_eq!(cards_to_text(&cards), "Hi (x)");
+    assert_eq!(cards_to_kmarkdown(&cards), "Hi \\(x\\)");
+    let greeting_kmd =
+        CardTemplate::from_cards(&vec![Card::new().section(Text::kmarkdown("Hi {{ name }}"))]);
+    for (name, escaped) in [
+        ("---", "\\-\\-\\-"),
+        (":tada:", "\\:tada\\:"),
+        ("hi!", "hi\\!"),
+        ("1. a", "1\\. a"),
+    ] {
+        let cards = greeting_kmd
+            .render(&serde_json::json!({ "name": name }))
+            .unwrap();
+        let json = serde_json::to_value(&cards).unwrap();
+        assert_eq!(
+            json[0]["modules"][0]["text"]["content"],
+            format!("Hi {}", escaped)
+        );
+    }
+    assert!(matches!(
+        greeting.render(&serde_json::json!({})),
+        Err(TemplateError::Missing(path)) if path == "name"
+    ));
+}
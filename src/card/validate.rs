@@ -0,0 +1,396 @@
+use std::fmt;
+use thiserror::Error;
+
+use super::*;
+
+const MAX_CARDS: usize = 5;
+const MAX_MODULES: usize = 50;
+const MAX_HEADER_LEN: usize = 100;
+const MAX_PLAIN_TEXT_LEN: usize = 2000;
+const MAX_KMARKDOWN_LEN: usize = 5000;
+const MAX_PARAGRAPH_FIELDS: usize = 50;
+const MAX_BUTTONS: usize = 4;
+const MAX_IMAGES: usize = 9;
+const MAX_CONTEXT_ELEMENTS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CardErrorKind {
+    #[error("{0} cards in a message, at most {MAX_CARDS} are allowed")]
+    TooManyCards(usize),
+    #[error("{0} modules in a message, at most {MAX_MODULES} are allowed")]
+    TooManyModules(usize),
+    #[error("`{element}` is not allowed in `{module}`")]
+    ElementNotAllowed {
+        module: &'static str,
+        element: &'static str,
+    },
+    #[error("{len} characters of `{element}`, at most {max} are allowed")]
+    TextTooLong {
+        element: &'static str,
+        len: usize,
+        max: usize,
+    },
+    #[error("paragraph has {0} columns, 1 to 3 are allowed")]
+    ParagraphColumns(u8),
+    #[error("paragraph has {0} fields, at most {MAX_PARAGRAPH_FIELDS} are allowed")]
+    TooManyFields(usize),
+    #[error("{module} has {len} elements, {min} to {max} are allowed")]
+    ElementCount {
+        module: &'static str,
+        len: usize,
+        min: usize,
+        max: usize,
+    },
+    #[error("a button accessory must be on the right")]
+    ButtonOnLeft,
+    #[error("countdown in `second` mode needs a start time")]
+    CountdownStart,
+    #[error("countdown starts at {start_time} after it ends at {end_time}")]
+    CountdownOrder { start_time: u64, end_time: u64 },
+}
+
+/// A limit KOOK would reject the cards for, `card` and `module` are indexes
+/// into the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardError {
+    pub card: Option<usize>,
+    pub module: Option<usize>,
+    pub kind: CardErrorKind,
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(card) = self.card {
+            write!(f, "card {}", card)?;
+            if let Some(module) = self.module {
+                write!(f, " module {}", module)?;
+            }
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl std::error::Error for CardError {}
+
+/// Check cards against the limits KOOK enforces server side.
+///
+/// ### Example:
+/// ```ignore
+/// if let Err(errors) = cards.validate() {
+///     for error in errors {
+///         tracing::warn!("{}", error);
+///     }
+/// }
+/// ```
+pub trait Validate {
+    /// Every violation found, in message order.
+    fn validate(&self) -> Result<(), Vec<CardError>>;
+}
+
+impl Validate for Cards {
+    fn validate(&self) -> Result<(), Vec<CardError>> {
+        let mut errors = vec![];
+        if self.len() > MAX_CARDS {
+            errors.push(CardError {
+                card: None,
+                module: None,
+                kind: CardErrorKind::TooManyCards(self.len()),
+            });
+        }
+        let modules: usize = self.iter().map(|c| c.modules.len()).sum();
+        if modules > MAX_MODULES {
+            errors.push(CardError {
+                card: None,
+                module: None,
+                kind: CardErrorKind::TooManyModules(modules),
+            });
+        }
+        for (index, card) in self.iter().enumerate() {
+            check_card(index, card, &mut errors);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Validate for Card {
+    fn validate(&self) -> Result<(), Vec<CardError>> {
+        let mut errors = vec![];
+        if self.modules.len() > MAX_MODULES {
+            errors.push(CardError {
+                card: Some(0),
+                module: None,
+                kind: CardErrorKind::TooManyModules(self.modules.len()),
+            });
+        }
+        check_card(0, self, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn module_name(module: &CardModule) -> &'static str {
+    match module {
+        CardModule::Header { .. } => "header",
+        CardModule::Section { .. } => "section",
+        CardModule::ImageGroup { .. } => "image-group",
+        CardModule::Container { .. } => "container",
+        CardModule::ActionGroup { .. } => "action-group",
+        CardModule::Context { .. } => "context",
+        CardModule::Divider => "divider",
+        CardModule::File { .. } => "file",
+        CardModule::Audio { .. } => "audio",
+        CardModule::Video { .. } => "video",
+        CardModule::Countdown { .. } => "countdown",
+        CardModule::Invite { .. } => "invite",
+    }
+}
+
+fn item_name(item: &CardItem) -> &'static str {
+    match item {
+        CardItem::PlainText { .. } => "plain-text",
+        CardItem::Kmarkdown { .. } => "kmarkdown",
+        CardItem::Image { .. } => "image",
+        CardItem::Button { .. } => "button",
+        CardItem::Paragraph { .. } => "paragraph",
+    }
+}
+
+fn check_card(card: usize, c: &Card, errors: &mut Vec<CardError>) {
+    for (index, module) in c.modules.iter().enumerate() {
+        let mut push = |kind| {
+            errors.push(CardError {
+                card: Some(card),
+                module: Some(index),
+                kind,
+            })
+        };
+        check_module(module, &mut push);
+    }
+}
+
+fn check_module(module: &CardModule, push: &mut dyn FnMut(CardErrorKind)) {
+    let name = module_name(module);
+    let allow = |items: &[CardItem], allowed: &[&str], push: &mut dyn FnMut(CardErrorKind)| {
+        for item in items {
+            let element = item_name(item);
+            if !allowed.contains(&element) {
+                push(CardErrorKind::ElementNotAllowed {
+                    module: name,
+                    element,
+                });
+            }
+        }
+    };
+    let count = |len: usize, min: usize, max: usize, push: &mut dyn FnMut(CardErrorKind)| {
+        if len < min || len > max {
+            push(CardErrorKind::ElementCount {
+                module: name,
+                len,
+                min,
+                max,
+            });
+        }
+    };
+    match module {
+        CardModule::Header { text } => {
+            allow(std::slice::from_ref(text), &["plain-text"], push);
+            if let CardItem::PlainText { content, .. } = text {
+                check_len("header", content, MAX_HEADER_LEN, push);
+            }
+        }
+        CardModule::Section {
+            text,
+            accessory,
+            mode,
+        } => {
+            allow(
+                std::slice::from_ref(text),
+                &["plain-text", "kmarkdown", "paragraph"],
+                push,
+            );
+            check_text(text, push);
+            if let Some(accessory) = accessory {
+                allow(std::slice::from_ref(accessory), &["image", "button"], push);
+            }
+            if let Some(accessory) = accessory {
+                check_text(accessory, push);
+                if *mode == Some(SectionMode::Left) && matches!(accessory, CardItem::Button { .. })
+                {
+                    push(CardErrorKind::ButtonOnLeft);
+                }
+            }
+        }
+        CardModule::ImageGroup { elements } | CardModule::Container { elements } => {
+            allow(elements, &["image"], push);
+            count(elements.len(), 1, MAX_IMAGES, push);
+        }
+        CardModule::ActionGroup { elements } => {
+            allow(elements, &["button"], push);
+            count(elements.len(), 1, MAX_BUTTONS, push);
+            elements.iter().for_each(|e| check_text(e, push));
+        }
+        CardModule::Context { elements } => {
+            allow(elements, &["plain-text", "kmarkdown", "image"], push);
+            count(elements.len(), 1, MAX_CONTEXT_ELEMENTS, push);
+            elements.iter().for_each(|e| check_text(e, push));
+        }
+        CardModule::Countdown {
+            mode,
+            end_time,
+            start_time,
+        } => match start_time {
+            None if *mode == CountdownMode::Second => push(CardErrorKind::CountdownStart),
+            Some(start_time) if start_time >= end_time => push(CardErrorKind::CountdownOrder {
+                start_time: *start_time,
+                end_time: *end_time,
+            }),
+            _ => {}
+        },
+        CardModule::Divider
+        | CardModule::File { .. }
+        | CardModule::Audio { .. }
+        | CardModule::Video { .. }
+        | CardModule::Invite { .. } => {}
+    }
+}
+
+fn check_text(item: &CardItem, push: &mut dyn FnMut(CardErrorKind)) {
+    match item {
+        CardItem::PlainText { content, .. } => {
+            check_len("plain-text", content, MAX_PLAIN_TEXT_LEN, push)
+        }
+        CardItem::Kmarkdown { content } => check_len("kmarkdown", content, MAX_KMARKDOWN_LEN, push),
+        CardItem::Paragraph { cols, fields } => {
+            if !(1..=3).contains(cols) {
+                push(CardErrorKind::ParagraphColumns(*cols));
+            }
+            if fields.len() > MAX_PARAGRAPH_FIELDS {
+                push(CardErrorKind::TooManyFields(fields.len()));
+            }
+            for field in fields {
+                match field {
+                    CardItem::PlainText { .. } | CardItem::Kmarkdown { .. } => {
+                        check_text(field, push)
+                    }
+                    _ => push(CardErrorKind::ElementNotAllowed {
+                        module: "paragraph",
+                        element: item_name(field),
+                    }),
+                }
+            }
+        }
+        CardItem::Button { text, .. } => match **text {
+            CardItem::PlainText { .. } | CardItem::Kmarkdown { .. } => check_text(text, push),
+            _ => push(CardErrorKind::ElementNotAllowed {
+                module: "button",
+                element: item_name(text),
+            }),
+        },
+        CardItem::Image { .. } => {}
+    }
+}
+
+fn check_len(
+    element: &'static str,
+    content: &str,
+    max: usize,
+    push: &mut dyn FnMut(CardErrorKind),
+) {
+    let len = content.chars().count();
+    if len > max {
+        push(CardErrorKind::TextTooLong { element, len, max });
+    }
+}
+
+#[test]
+fn validate_test() {
+    let card = Card::new()
+        .header("Title")
+        .section(Paragraph::new(2).field("a").field("b"))
+        .action_group([Button::new("A", "a")]);
+    assert!(vec![card.clone()].validate().is_ok());
+
+    let cards = vec![
+        card.clone()
+            .header("x".repeat(101))
+            .section(Paragraph::new(4).field("a"))
+            .action_group((0..5).map(|i| Button::new("b", i.to_string())))
+            .image_group([])
+            .module(CardModule::Section {
+                mode: Some(SectionMode::Left),
+                text: Text::from("text").into(),
+                accessory: Some(Button::new("b", "b").into()),
+            })
+            .module(CardModule::Countdown {
+                mode: CountdownMode::Second,
+                end_time: 1,
+                start_time: None,
+            })
+            .module(CardModule::Countdown {
+                mode: CountdownMode::Day,
+                end_time: 1,
+                start_time: Some(2),
+            }),
+        card,
+    ];
+    let errors = cards.validate().unwrap_err();
+    let kinds: Vec<_> = errors.iter().map(|e| (e.module, &e.kind)).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (
+                Some(3),
+                &CardErrorKind::TextTooLong {
+                    element: "header",
+                    len: 101,
+                    max: 100
+                }
+            ),
+            (Some(4), &CardErrorKind::ParagraphColumns(4)),
+            (
+                Some(5),
+                &CardErrorKind::ElementCount {
+                    module: "action-group",
+                    len: 5,
+                    min: 1,
+                    max: 4
+                }
+            ),
+            (
+                Some(6),
+                &CardErrorKind::ElementCount {
+                    module: "image-group",
+                    len: 0,
+                    min: 1,
+                    max: 9
+                }
+            ),
+            (Some(7), &CardErrorKind::ButtonOnLeft),
+            (Some(8), &CardErrorKind::CountdownStart),
+            (
+                Some(9),
+                &CardErrorKind::CountdownOrder {
+                    start_time: 2,
+                    end_time: 1
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        errors[1].to_string(),
+        "card 0 module 4: paragraph has 4 columns, 1 to 3 are allowed"
+    );
+
+    let many = vec![Card::new().divider(); 6];
+    let errors = many.validate().unwrap_err();
+    assert_eq!(errors[0].card, None);
+    assert_eq!(errors[0].kind, CardErrorKind::TooManyCards(6));
+}
@@ -0,0 +1,144 @@
+use dashmap::DashMap;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+use crate::prelude::*;
+
+/// Handlers waiting for a future event, fed by `Kook::submit` before the event is queued.
+#[derive(Default)]
+pub(crate) struct Collectors {
+    next_id: AtomicU64,
+    waiting: DashMap<u64, (Filter, oneshot::Sender<Event<EventExtra>>)>,
+}
+
+impl Collectors {
+    pub(crate) fn feed(&self, event: &Event<EventExtra>) {
+        if self.waiting.is_empty() {
+            return;
+        }
+        let matched: Vec<u64> = self
+            .waiting
+            .iter()
+            .filter(|entry| entry.value().0.matches(event))
+            .map(|entry| *entry.key())
+            .collect();
+        for id in matched {
+            if let Some((_, (_, tx))) = self.waiting.remove(&id) {
+                tx.send(event.clone()).ok();
+            }
+        }
+    }
+}
+
+/// Removes the waiter when the future is dropped or times out.
+struct Guard<'a>(&'a Collectors, u64);
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        self.0.waiting.remove(&self.1);
+    }
+}
+
+impl Kook {
+    /// Wait for the next event matching `filter`, `None` on timeout.
+    pub async fn wait_for(&self, filter: Filter, timeout: Duration) -> Option<Event<EventExtra>> {
+        let (tx, rx) = oneshot::channel();
+        let id = self.collectors.next_id.fetch_add(1, Ordering::Relaxed);
+        self.collectors.waiting.insert(id, (filter, tx));
+        let _guard = Guard(&self.collectors, id);
+        tokio::time::timeout(timeout, rx).await.ok()?.ok()
+    }
+
+    /// Wait for a reaction added to `msg_id` accepted by `filter`,
+    /// returns the user id and the emoji.
+    pub async fn wait_for_reaction<F>(
+        &self,
+        msg_id: &str,
+        filter: F,
+        timeout: Duration,
+    ) -> Option<(String, Emoji)>
+    where
+        F: Fn(&str, &Emoji) -> bool + Send + Sync + 'static,
+    {
+        let msg_id = msg_id.to_owned();
+        let filter = Filter::new(move |e| match added_reaction(e) {
+            Some((m, user_id, emoji)) => m == msg_id && filter(user_id, emoji),
+            None => false,
+        });
+        let event = self.wait_for(filter, timeout).await?;
+        added_reaction(&event).map(|(_, user_id, emoji)| (user_id.to_owned(), emoji.clone()))
+    }
+
+    /// Wait for a click on a button of `msg_id`.
+    pub async fn wait_for_button(
+        &self,
+        msg_id: &str,
+        timeout: Duration,
+    ) -> Option<MessageBtnClick> {
+        let msg_id = msg_id.to_owned();
+        let filter = Filter::new(move |e| match &e.extra {
+            EventExtra::System(SystemExtra::MessageBtnClick(b)) => b.msg_id == msg_id,
+            _ => false,
+        });
+        match self.wait_for(filter, timeout).await?.extra {
+            EventExtra::System(SystemExtra::MessageBtnClick(b)) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+fn added_reaction(event: &Event<EventExtra>) -> Option<(&str, &str, &Emoji)> {
+    match &event.extra {
+        EventExtra::System(SystemExtra::AddedReaction(r)) => {
+            Some((&r.msg_id, &r.user_id, &r.emoji))
+        }
+        EventExtra::System(SystemExtra::PrivateAddedReaction(r)) => {
+            Some((&r.msg_id, &r.user_id, &r.emoji))
+        }
+        _ => None,
+    }
+}
+
+impl Context {
+    /// Wait for the next message from `user_id` where this event happened.
+    pub async fn wait_for_reply(
+        &self,
+        user_id: &str,
+        timeout: Duration,
+    ) -> Option<Event<EventExtra>> {
+        let filter = match &self.channel_id {
+            Some(channel_id) => Filter::channel(channel_id).and(Filter::group_message()),
+            None => Filter::person_message(),
+        };
+        self.wait_for(filter.and(Filter::author(user_id)), timeout)
+            .await
+    }
+}
+
+#[tokio::test]
+async fn collector_test() {
+    let kook = Kook::new_from_config(Config::default(), ()).arc();
+    let event = crate::test::added_reaction();
+    let waiter = {
+        let kook = kook.clone();
+        tokio::spawn(async move {
+            kook.wait_for_reaction("msg", |_, emoji| emoji.id == "1", Duration::from_secs(5))
+                .await
+        })
+    };
+    while kook.collectors.waiting.is_empty() {
+        tokio::task::yield_now().await;
+    }
+    kook.submit(event);
+    let (user_id, emoji) = waiter.await.unwrap().unwrap();
+    assert_eq!(user_id, "user");
+    assert_eq!(emoji.name, "smile");
+    assert!(kook.collectors.waiting.is_empty());
+
+    let timeout = kook.wait_for_button("msg", Duration::from_millis(10)).await;
+    assert!(timeout.is_none());
+    assert!(kook.collectors.waiting.is_empty());
+}
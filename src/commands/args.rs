@@ -0,0 +1,332 @@
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::KMDItem;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    Text(String),
+    Mention(String),
+    Channel(String),
+    Role(String),
+}
+
+impl Arg {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Text(s) | Self::Mention(s) | Self::Channel(s) | Self::Role(s) => s,
+        }
+    }
+}
+
+impl std::fmt::Display for Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(s) => f.write_str(s),
+            Self::Mention(s) => write!(f, "(met){s}(met)"),
+            Self::Channel(s) => write!(f, "(chn){s}(chn)"),
+            Self::Role(s) => write!(f, "(rol){s}(rol)"),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ArgError {
+    #[error("missing argument <{0}>")]
+    Missing(&'static str),
+    #[error("invalid {expected}: {found}")]
+    Invalid {
+        expected: &'static str,
+        found: String,
+    },
+}
+
+/// Arguments of a command, split on whitespace with `"quoted strings"` kept together.
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    /// Each argument with its start in `raw`.
+    items: Vec<(usize, Arg)>,
+    raw: String,
+    pos: usize,
+}
+
+impl Args {
+    pub fn from_kmd(items: &[KMDItem]) -> Self {
+        let mut args = Self::default();
+        let mut text_start = 0;
+        for item in items {
+            let arg = match item {
+                KMDItem::Mention(id) => Arg::Mention(id.clone()),
+                KMDItem::Channel(id) => Arg::Channel(id.clone()),
+                KMDItem::Role(id) => Arg::Role(id.clone()),
+                KMDItem::Text(s) => {
+                    args.raw.push_str(s);
+                    continue;
+                }
+                item => {
+                    args.raw.push_str(&item.to_string());
+                    continue;
+                }
+            };
+            args.items
+                .extend(split_text(&args.raw[text_start..], text_start));
+            args.items.push((args.raw.len(), arg.clone()));
+            args.raw.push_str(&arg.to_string());
+            text_start = args.raw.len();
+        }
+        args.items
+            .extend(split_text(&args.raw[text_start..], text_start));
+        args
+    }
+
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            items: split_text(text, 0),
+            raw: text.to_owned(),
+            pos: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len() - self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn peek(&self) -> Option<&Arg> {
+        self.items.get(self.pos).map(|(_, arg)| arg)
+    }
+
+    pub fn next_arg(&mut self) -> Option<Arg> {
+        let arg = self.peek().cloned();
+        if arg.is_some() {
+            self.pos += 1;
+        }
+        arg
+    }
+
+    /// Parse the next argument, `name` is used in the error when it is missing.
+    pub fn next<T: FromArg>(&mut self, name: &'static str) -> Result<T, ArgError> {
+        let arg = self.peek().ok_or(ArgError::Missing(name))?;
+        let value = T::from_arg(arg)?;
+        self.pos += 1;
+        Ok(value)
+    }
+
+    /// Parse the next argument if there is one.
+    pub fn optional<T: FromArg>(&mut self) -> Result<Option<T>, ArgError> {
+        match self.peek() {
+            Some(arg) => {
+                let value = T::from_arg(arg)?;
+                self.pos += 1;
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The remaining text as sent, with quotes, spacing and newlines kept.
+    pub fn rest(&mut self) -> String {
+        let rest = match self.items.get(self.pos) {
+            Some((start, _)) => self.raw[*start..].trim_end().to_owned(),
+            None => String::new(),
+        };
+        self.pos = self.items.len();
+        rest
+    }
+}
+
+/// Split `text` found at `offset` in the raw message.
+fn split_text(text: &str, offset: usize) -> Vec<(usize, Arg)> {
+    let mut items = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                start.get_or_insert(offset + i);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if let Some(start) = start.take() {
+                    items.push((start, Arg::Text(std::mem::take(&mut current))));
+                }
+            }
+            c => {
+                current.push(c);
+                start.get_or_insert(offset + i);
+            }
+        }
+    }
+    if let Some(start) = start {
+        items.push((start, Arg::Text(current)));
+    }
+    items
+}
+
+pub trait FromArg: Sized {
+    fn from_arg(arg: &Arg) -> Result<Self, ArgError>;
+}
+
+fn invalid(expected: &'static str, arg: &Arg) -> ArgError {
+    ArgError::Invalid {
+        expected,
+        found: arg.to_string(),
+    }
+}
+
+impl FromArg for String {
+    fn from_arg(arg: &Arg) -> Result<Self, ArgError> {
+        Ok(arg.to_string())
+    }
+}
+
+impl FromArg for Arg {
+    fn from_arg(arg: &Arg) -> Result<Self, ArgError> {
+        Ok(arg.clone())
+    }
+}
+
+macro_rules! from_str_arg {
+    ($ty: ty, $expected: expr) => {
+        impl FromArg for $ty {
+            fn from_arg(arg: &Arg) -> Result<Self, ArgError> {
+                match arg {
+                    Arg::Text(s) => s.parse().map_err(|_| invalid($expected, arg)),
+                    _ => Err(invalid($expected, arg)),
+                }
+            }
+        }
+    };
+}
+
+from_str_arg!(i32, "integer");
+from_str_arg!(i64, "integer");
+from_str_arg!(u32, "integer");
+from_str_arg!(u64, "integer");
+from_str_arg!(f64, "number");
+from_str_arg!(bool, "bool");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserMention(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelMention(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleMention(pub String);
+
+impl FromArg for UserMention {
+    fn from_arg(arg: &Arg) -> Result<Self, ArgError> {
+        match arg {
+            Arg::Mention(id) => Ok(Self(id.clone())),
+            _ => Err(invalid("user mention", arg)),
+        }
+    }
+}
+
+impl FromArg for ChannelMention {
+    fn from_arg(arg: &Arg) -> Result<Self, ArgError> {
+        match arg {
+            Arg::Channel(id) => Ok(Self(id.clone())),
+            _ => Err(invalid("channel mention", arg)),
+        }
+    }
+}
+
+impl FromArg for RoleMention {
+    fn from_arg(arg: &Arg) -> Result<Self, ArgError> {
+        match arg {
+            Arg::Role(id) => Ok(Self(id.clone())),
+            _ => Err(invalid("role mention", arg)),
+        }
+    }
+}
+
+/// Accepts plain seconds (`90`) or units `d`, `h`, `m`, `s` (`1h30m`).
+impl FromArg for Duration {
+    fn from_arg(arg: &Arg) -> Result<Self, ArgError> {
+        let s = match arg {
+            Arg::Text(s) => s,
+            _ => return Err(invalid("duration", arg)),
+        };
+        if let Ok(secs) = s.parse() {
+            return Ok(Duration::from_secs(secs));
+        }
+        let mut secs = 0;
+        let mut num = String::new();
+        for c in s.chars() {
+            if c.is_ascii_digit() {
+                num.push(c);
+                continue;
+            }
+            let unit = match c {
+                'd' => 86400,
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                _ => return Err(invalid("duration", arg)),
+            };
+            let n: u64 = num.parse().map_err(|_| invalid("duration", arg))?;
+            secs = n
+                .checked_mul(unit)
+                .and_then(|n| n.checked_add(secs))
+                .ok_or_else(|| invalid("duration", arg))?;
+            num.clear();
+        }
+        if !num.is_empty() || s.is_empty() {
+            return Err(invalid("duration", arg));
+        }
+        Ok(Duration::from_secs(secs))
+    }
+}
+
+#[test]
+fn args_test() {
+    let kmds =
+        crate::kmd_from_str(r#"(met)123(met) "hello world" 42 1h30m (chn)456(chn) tail text"#);
+    let mut args = Args::from_kmd(&kmds);
+    assert_eq!(args.len(), 7);
+    assert_eq!(
+        args.next::<UserMention>("user").unwrap(),
+        UserMention("123".to_owned())
+    );
+    assert_eq!(args.next::<String>("msg").unwrap(), "hello world");
+    assert!(args.peek().is_some());
+    assert!(matches!(
+        args.next::<UserMention>("user"),
+        Err(ArgError::Invalid { .. })
+    ));
+    assert_eq!(args.next::<i64>("count").unwrap(), 42);
+    assert_eq!(
+        args.next::<Duration>("time").unwrap(),
+        Duration::from_secs(5400)
+    );
+    assert_eq!(
+        args.next::<ChannelMention>("channel").unwrap(),
+        ChannelMention("456".to_owned())
+    );
+    assert_eq!(args.rest(), "tail text");
+    assert_eq!(args.next::<i64>("count"), Err(ArgError::Missing("count")));
+    assert_eq!(args.optional::<i64>(), Ok(None));
+    assert_eq!(args.rest(), "");
+
+    let mut args = Args::from_text("echo \"a  b\"\n  next line  ");
+    assert_eq!(args.next::<String>("name").unwrap(), "echo");
+    assert_eq!(args.rest(), "\"a  b\"\n  next line");
+
+    let kmds = crate::kmd_from_str("hi (met)1(met)  there");
+    let mut args = Args::from_kmd(&kmds);
+    args.next_arg();
+    assert_eq!(args.rest(), "(met)1(met)  there");
+
+    for overflow in ["999999999999999d", "18446744073709551615s1s"] {
+        assert!(matches!(
+            Args::from_text(overflow).next::<Duration>("time"),
+            Err(ArgError::Invalid { .. })
+        ));
+    }
+}
@@ -0,0 +1,299 @@
+mod args;
+
+pub use args::*;
+
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc};
+use thiserror::Error;
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+use crate::card::{Card, Cards};
+use crate::prelude::*;
+use crate::{kmd_from_str, KMDItem};
+
+pub type CommandResult = Result<(), CommandError>;
+
+#[derive(Debug, Error)]
+pub enum CommandError {
+    #[error("{0}")]
+    Arg(#[from] ArgError),
+    #[error("{0}")]
+    Kook(#[from] KookError),
+    #[error("{0}")]
+    Custom(String),
+}
+
+#[async_trait]
+pub trait CommandHandler: Sync + Send {
+    async fn call(&self, ctx: &Context, event: &Event<EventExtra>, args: Args) -> CommandResult;
+}
+
+pub struct Command {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub description: String,
+    pub usage: String,
+    handler: Arc<dyn CommandHandler>,
+}
+
+impl Command {
+    pub fn new<H>(name: &str, handler: H) -> Self
+    where
+        H: CommandHandler + 'static,
+    {
+        Self {
+            name: name.to_owned(),
+            aliases: vec![],
+            description: String::default(),
+            usage: String::default(),
+            handler: Arc::new(handler),
+        }
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.to_owned());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_owned();
+        self
+    }
+
+    pub fn usage(mut self, usage: &str) -> Self {
+        self.usage = usage.to_owned();
+        self
+    }
+}
+
+/// An `EventHandler` dispatching message events to registered commands.
+///
+/// ### Example:
+/// ```ignore
+/// let commands = Commands::new()
+///     .prefix("/")
+///     .command(Command::new("echo", Echo).alias("e").usage("<text>"));
+/// let kook = Kook::new_from_config(config, commands).arc();
+/// ```
+pub struct Commands {
+    prefixes: Vec<String>,
+    mention_prefix: bool,
+    ignore_bots: bool,
+    help: bool,
+    commands: Vec<Command>,
+    names: HashMap<String, usize>,
+    bot_id: OnceCell<String>,
+}
+
+impl Default for Commands {
+    fn default() -> Self {
+        Self {
+            prefixes: vec![],
+            mention_prefix: true,
+            ignore_bots: true,
+            help: true,
+            commands: vec![],
+            names: HashMap::default(),
+            bot_id: OnceCell::default(),
+        }
+    }
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefixes.push(prefix.to_owned());
+        self
+    }
+
+    /// Whether `@bot cmd` triggers a command, default `true`.
+    pub fn mention_prefix(mut self, enable: bool) -> Self {
+        self.mention_prefix = enable;
+        self
+    }
+
+    /// Whether messages sent by bots are ignored, default `true`.
+    pub fn ignore_bots(mut self, enable: bool) -> Self {
+        self.ignore_bots = enable;
+        self
+    }
+
+    /// Whether the builtin `help` command is enabled, default `true`.
+    pub fn help(mut self, enable: bool) -> Self {
+        self.help = enable;
+        self
+    }
+
+    /// Set the bot id used to match mention prefix instead of calling `get_me`.
+    pub fn bot_id(self, bot_id: &str) -> Self {
+        self.bot_id.set(bot_id.to_owned()).ok();
+        self
+    }
+
+    pub fn command(mut self, command: Command) -> Self {
+        let index = self.commands.len();
+        self.names.insert(command.name.clone(), index);
+        for alias in &command.aliases {
+            self.names.insert(alias.clone(), index);
+        }
+        self.commands.push(command);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.names.get(name).map(|i| &self.commands[*i])
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Split a message into command name and arguments if it is a command.
+    pub fn parse(&self, event: &Event<EventExtra>, bot_id: Option<&str>) -> Option<(String, Args)> {
+        let mut items = if event.ty == MessageType::KMarkdown {
+            kmd_from_str(&event.content)
+        } else {
+            vec![KMDItem::Text(event.content.clone())]
+        };
+        let mut prefixed = false;
+        if let (true, Some(KMDItem::Mention(id)), Some(bot_id)) =
+            (self.mention_prefix, items.first(), bot_id)
+        {
+            if id == bot_id {
+                items.remove(0);
+                prefixed = true;
+            }
+        }
+        if let Some(KMDItem::Text(text)) = items.first_mut() {
+            let trimmed = text.trim_start();
+            if let Some(rest) = self
+                .prefixes
+                .iter()
+                .find_map(|p| trimmed.strip_prefix(p.as_str()))
+            {
+                *text = rest.to_owned();
+                prefixed = true;
+            }
+        }
+        if !prefixed {
+            return None;
+        }
+        let mut args = Args::from_kmd(&items);
+        match args.next_arg()? {
+            Arg::Text(name) => Some((name, args)),
+            _ => None,
+        }
+    }
+
+    pub async fn dispatch(&self, ctx: &Context, event: &Event<EventExtra>) -> CommandResult {
+        match event.author() {
+            Some(author) if !(self.ignore_bots && author.bot) => {}
+            _ => return Ok(()),
+        }
+        let bot_id = if self.mention_prefix && event.content.starts_with("(met)") {
+            self.bot_id
+                .get_or_try_init(|| async { ctx.get_me().await.map(|me| me.id) })
+                .await
+                .ok()
+                .map(String::as_str)
+        } else {
+            None
+        };
+        let (name, args) = match self.parse(event, bot_id) {
+            Some(parsed) => parsed,
+            None => return Ok(()),
+        };
+        let command = match self.get(&name) {
+            Some(command) => command,
+            None if self.help && name == "help" => {
+                ctx.reply_cards(&self.help_cards()).await?;
+                return Ok(());
+            }
+            None => {
+                debug!(target: KOOK, "unknown command {}", name);
+                return Ok(());
+            }
+        };
+        match command.handler.call(ctx, event, args).await {
+            Err(CommandError::Arg(e)) => {
+                let prefix = self
+                    .prefixes
+                    .first()
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                let content = format!("{}\nusage: {}{} {}", e, prefix, command.name, command.usage);
+                ctx.reply_with(MessageType::Text, &content).await?;
+                Ok(())
+            }
+            r => r,
+        }
+    }
+
+    pub fn help_cards(&self) -> Cards {
+        let prefix = self
+            .prefixes
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default();
+        let mut card = Card::new().header("Commands").divider();
+        for command in &self.commands {
+            let mut content = format!("**{}{}**", prefix, command.name);
+            if !command.usage.is_empty() {
+                content.push_str(&format!(" `{}`", command.usage));
+            }
+            if !command.aliases.is_empty() {
+                content.push_str(&format!("\naliases: {}", command.aliases.join(", ")));
+            }
+            if !command.description.is_empty() {
+                content.push('\n');
+                content.push_str(&command.description);
+            }
+            card = card.section(content);
+        }
+        vec![card]
+    }
+}
+
+#[async_trait]
+impl EventHandler for Commands {
+    async fn handle(&self, ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+        Ok(self.dispatch(ctx, &event).await?)
+    }
+}
+
+#[test]
+fn parse_test() {
+    struct Nop;
+
+    #[async_trait]
+    impl CommandHandler for Nop {
+        async fn call(&self, _: &Context, _: &Event<EventExtra>, _: Args) -> CommandResult {
+            Ok(())
+        }
+    }
+
+    let commands = Commands::new()
+        .prefix("/")
+        .command(Command::new("echo", Nop).alias("e").usage("<text>"));
+    let mut event = crate::test::group_message("(met)bot(met) echo \"hello world\" 1");
+    let (name, mut args) = commands.parse(&event, Some("bot")).unwrap();
+    assert_eq!(name, "echo");
+    assert_eq!(args.next::<String>("text").unwrap(), "hello world");
+    assert_eq!(args.next::<i32>("n").unwrap(), 1);
+    assert!(commands.parse(&event, Some("other")).is_none());
+
+    event.content = "/e hi".to_owned();
+    let (name, mut args) = commands.parse(&event, None).unwrap();
+    assert_eq!(commands.get(&name).unwrap().name, "echo");
+    assert_eq!(args.rest(), "hi");
+
+    event.content = "echo hi".to_owned();
+    assert!(commands.parse(&event, None).is_none());
+
+    let help = commands.help_cards();
+    assert_eq!(help[0].modules.len(), 3);
+}
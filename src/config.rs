@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
 
+use crate::dispatch::DispatchMode;
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     pub bot_token: String,
     pub bot_block: bool,
+    #[serde(default)]
+    pub dispatch: DispatchMode,
 }
 
 impl Config {
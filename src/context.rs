@@ -0,0 +1,218 @@
+use std::{ops::Deref, sync::Arc};
+
+use crate::card::{cards_encode, Cards};
+use crate::prelude::*;
+
+/// Passed to every handler along with the event, knows where the event
+/// came from so replies go back to the same channel or direct chat.
+#[derive(Clone)]
+pub struct Context {
+    kook: Arc<Kook>,
+    /// Channel the event happened in, `None` for direct messages.
+    pub channel_id: Option<String>,
+    pub guild_id: Option<String>,
+    /// User who triggered the event.
+    pub user_id: String,
+    /// Message the event is about.
+    pub msg_id: Option<String>,
+    /// The message before an edit or delete, when the message cache is enabled
+    /// and has seen it.
+    pub previous_message: Option<CachedMessage>,
+    channel_type: EventChannelType,
+}
+
+impl Deref for Context {
+    type Target = Kook;
+
+    fn deref(&self) -> &Self::Target {
+        &self.kook
+    }
+}
+
+impl Context {
+    pub fn new(kook: Arc<Kook>, event: &Event<EventExtra>) -> Self {
+        // clicks always come as `PERSON`, the body tells where the card is
+        let channel_type = match &event.extra {
+            EventExtra::System(SystemExtra::MessageBtnClick(b)) => {
+                b.channel_type.unwrap_or(event.channel_type)
+            }
+            _ => event.channel_type,
+        };
+        let channel_id = match channel_type {
+            EventChannelType::Group => event.channel_id().map(ToOwned::to_owned),
+            _ => None,
+        };
+        let (user_id, msg_id) = match &event.extra {
+            EventExtra::System(s) => (
+                s.user_id().unwrap_or(&event.author_id).to_owned(),
+                s.msg_id().map(ToOwned::to_owned),
+            ),
+            _ => (event.author_id.clone(), Some(event.msg_id.clone())),
+        };
+        Self {
+            kook,
+            channel_id,
+            guild_id: event.guild_id().map(ToOwned::to_owned),
+            user_id,
+            msg_id,
+            previous_message: None,
+            channel_type,
+        }
+    }
+
+    pub fn kook(&self) -> &Arc<Kook> {
+        &self.kook
+    }
+
+    fn event_msg_id(&self) -> KookResult<&str> {
+        self.msg_id
+            .as_deref()
+            .ok_or(KookError::MissingEventField("msg_id"))
+    }
+
+    /// The channel to act in, `None` for direct messages. Guild events with
+    /// no channel have nowhere to reply to.
+    fn channel(&self) -> KookResult<Option<&str>> {
+        match (&self.channel_id, self.channel_type) {
+            (Some(channel_id), _) => Ok(Some(channel_id)),
+            (None, EventChannelType::Person) => Ok(None),
+            (None, _) => Err(KookError::MissingEventField("channel_id")),
+        }
+    }
+
+    async fn send(
+        &self,
+        ty: MessageType,
+        content: &str,
+        quote: Option<&str>,
+        temp: bool,
+    ) -> KookResult<MessageResp> {
+        match self.channel()? {
+            Some(channel_id) => {
+                let temp_target_id = temp.then_some(self.user_id.as_str());
+                self.create_message(Some(ty), channel_id, content, quote, None, temp_target_id)
+                    .await
+            }
+            None => {
+                self.create_direct_message(
+                    Some(&self.user_id),
+                    None,
+                    content,
+                    Some(ty),
+                    quote,
+                    None,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Send a KMarkdown message to where the event came from.
+    pub async fn reply(&self, content: &str) -> KookResult<MessageResp> {
+        self.send(MessageType::KMarkdown, content, None, false)
+            .await
+    }
+
+    pub async fn reply_with(&self, ty: MessageType, content: &str) -> KookResult<MessageResp> {
+        self.send(ty, content, None, false).await
+    }
+
+    pub async fn reply_cards(&self, cards: &Cards) -> KookResult<MessageResp> {
+        self.send(MessageType::Card, &cards_encode(cards), None, false)
+            .await
+    }
+
+    /// Reply quoting the message of the event.
+    pub async fn reply_quote(&self, content: &str) -> KookResult<MessageResp> {
+        let msg_id = self.event_msg_id()?;
+        self.send(MessageType::KMarkdown, content, Some(msg_id), false)
+            .await
+    }
+
+    /// Reply with a temporary message only visible to the user, a normal
+    /// direct message if the event came from one.
+    pub async fn reply_temp(&self, content: &str) -> KookResult<MessageResp> {
+        self.send(MessageType::KMarkdown, content, None, true).await
+    }
+
+    /// Add a reaction to the message of the event.
+    pub async fn react(&self, emoji: &str) -> KookResult<()> {
+        let msg_id = self.event_msg_id()?;
+        match self.channel()? {
+            Some(_) => self.add_message_reaction(msg_id, emoji).await,
+            None => self.add_direct_message_reaction(msg_id, emoji).await,
+        }
+    }
+
+    /// Delete the message of the event.
+    pub async fn delete(&self) -> KookResult<()> {
+        let msg_id = self.event_msg_id()?;
+        match self.channel()? {
+            Some(_) => self.delete_message(msg_id).await,
+            None => self.delete_direct_message(Some(msg_id)).await,
+        }
+    }
+
+    pub async fn dm_author(&self, content: &str) -> KookResult<MessageResp> {
+        self.create_direct_message(
+            Some(&self.user_id),
+            None,
+            content,
+            Some(MessageType::KMarkdown),
+            None,
+            None,
+        )
+        .await
+    }
+}
+
+#[test]
+fn context_test() {
+    let khl = Kook::new_from_config(Config::default(), ()).arc();
+    let event = crate::test::added_reaction();
+    let ctx = Context::new(khl, &event);
+    assert_eq!(ctx.channel_id.as_deref(), Some("channel"));
+    assert_eq!(ctx.guild_id.as_deref(), Some("guild"));
+    assert_eq!(ctx.user_id, "user");
+    assert_eq!(ctx.msg_id.as_deref(), Some("msg"));
+}
+
+#[test]
+fn click_context_test() {
+    let khl = Kook::new_from_config(Config::default(), ()).arc();
+    let mut event = crate::test::button_click("ok", "user");
+    let ctx = Context::new(khl.clone(), &event);
+    assert_eq!(ctx.channel_id.as_deref(), Some("channel"));
+    assert_eq!(ctx.guild_id.as_deref(), Some("guild"));
+    assert_eq!(ctx.user_id, "user");
+    assert_eq!(ctx.msg_id.as_deref(), Some("msg"));
+
+    if let EventExtra::System(SystemExtra::MessageBtnClick(b)) = &mut event.extra {
+        b.channel_type = Some(EventChannelType::Person);
+        b.guild_id = None;
+    }
+    let ctx = Context::new(khl, &event);
+    assert_eq!(ctx.channel_id, None);
+    assert_eq!(ctx.guild_id, None);
+}
+
+#[tokio::test]
+async fn guild_event_reply_test() {
+    let khl = Kook::new_from_config(Config::default(), ()).arc();
+    let event = crate::test::system_event(
+        "guild",
+        serde_json::json!({
+            "type": "added_role",
+            "body": {
+                "role_id": 1, "name": "role", "color": 0, "position": 1,
+                "hoist": 0, "mentionable": 0, "permissions": 0
+            }
+        }),
+    );
+    let ctx = Context::new(khl, &event);
+    assert_eq!(ctx.channel_id, None);
+    assert!(matches!(
+        ctx.reply("hi").await,
+        Err(KookError::MissingEventField("channel_id"))
+    ));
+}
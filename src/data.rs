@@ -0,0 +1,106 @@
+use dashmap::DashMap;
+use std::{
+    any::{Any, TypeId},
+    sync::Arc,
+};
+
+use crate::Kook;
+
+/// A map holding at most one value of each type.
+#[derive(Default)]
+pub struct TypeMap(DashMap<TypeId, Arc<dyn Any + Send + Sync>>);
+
+impl TypeMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value, returning the previous value of the same type.
+    pub fn insert<T>(&self, value: T) -> Option<Arc<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.0
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .and_then(|old| old.downcast().ok())
+    }
+
+    pub fn get<T>(&self) -> Option<Arc<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.0
+            .get(&TypeId::of::<T>())
+            .and_then(|v| v.value().clone().downcast().ok())
+    }
+
+    pub fn remove<T>(&self) -> Option<Arc<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.0
+            .remove(&TypeId::of::<T>())
+            .and_then(|(_, v)| v.downcast().ok())
+    }
+
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        self.0.contains_key(&TypeId::of::<T>())
+    }
+}
+
+impl Kook {
+    /// Attach shared state before starting, available to handlers through `data`.
+    pub fn with_data<T>(self, value: T) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.data.insert(value);
+        self
+    }
+
+    pub fn insert_data<T>(&self, value: T) -> Option<Arc<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.data.insert(value)
+    }
+
+    pub fn data<T>(&self) -> Option<Arc<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.data.get()
+    }
+
+    pub fn type_map(&self) -> &TypeMap {
+        &self.data
+    }
+}
+
+#[test]
+fn type_map_test() {
+    use crate::config::Config;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, PartialEq)]
+    struct Prefix(&'static str);
+
+    let kook = Kook::new_from_config(Config::default(), ())
+        .with_data(Prefix("!"))
+        .with_data(AtomicUsize::new(1));
+    assert_eq!(*kook.data::<Prefix>().unwrap(), Prefix("!"));
+    kook.data::<AtomicUsize>()
+        .unwrap()
+        .fetch_add(1, Ordering::SeqCst);
+    assert_eq!(
+        kook.data::<AtomicUsize>().unwrap().load(Ordering::SeqCst),
+        2
+    );
+    assert_eq!(kook.insert_data(Prefix("/")), Some(Arc::new(Prefix("!"))));
+    assert!(kook.data::<String>().is_none());
+    assert!(kook.type_map().remove::<Prefix>().is_some());
+    assert!(!kook.type_map().contains::<Prefix>());
+}
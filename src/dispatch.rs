@@ -0,0 +1,323 @@
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Weak,
+};
+use tokio::sync::{mpsc, Semaphore};
+use tracing::error;
+
+use crate::prelude::*;
+
+/// How events received from the gateway are scheduled onto handlers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DispatchMode {
+    /// Every event in its own task, at most `max_in_flight` at once, `0` for no limit.
+    Concurrent { max_in_flight: usize },
+    /// Events with the same key are handled one at a time in arrival order,
+    /// events with different keys concurrently.
+    Ordered { by: OrderKey },
+    /// One event at a time in arrival order.
+    Sequential,
+}
+
+impl Default for DispatchMode {
+    fn default() -> Self {
+        Self::Concurrent { max_in_flight: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderKey {
+    /// The channel, or the author for direct messages.
+    Channel,
+    /// The guild, or the author for direct messages.
+    Guild,
+    User,
+}
+
+impl OrderKey {
+    fn key(self, event: &Event<EventExtra>) -> String {
+        let id = match self {
+            Self::Channel => event.channel_id(),
+            Self::Guild => event.guild_id(),
+            Self::User => None,
+        };
+        id.unwrap_or(&event.author_id).to_owned()
+    }
+}
+
+/// An event with the message it replaced in the message cache.
+type Queued = (Event<EventExtra>, Option<CachedMessage>);
+
+pub(crate) struct Queue {
+    tx: mpsc::UnboundedSender<Queued>,
+    pending: Arc<AtomicUsize>,
+}
+
+/// Removes the queue of a `run_queue` task that ended without draining it,
+/// e.g. by a panic, so later events of its key start a new one.
+struct QueueGuard {
+    queues: Arc<DashMap<String, Queue>>,
+    key: String,
+    pending: Arc<AtomicUsize>,
+}
+
+impl Drop for QueueGuard {
+    fn drop(&mut self) {
+        self.queues
+            .remove_if(&self.key, |_, q| Arc::ptr_eq(&q.pending, &self.pending));
+    }
+}
+
+pub(crate) enum Dispatcher {
+    Concurrent(Option<Arc<Semaphore>>),
+    Ordered(Option<OrderKey>, Arc<DashMap<String, Queue>>),
+}
+
+impl Dispatcher {
+    pub(crate) fn new(mode: DispatchMode) -> Self {
+        match mode {
+            DispatchMode::Concurrent { max_in_flight: 0 } => Self::Concurrent(None),
+            DispatchMode::Concurrent { max_in_flight } => {
+                Self::Concurrent(Some(Arc::new(Semaphore::new(max_in_flight))))
+            }
+            DispatchMode::Ordered { by } => Self::Ordered(Some(by), Arc::default()),
+            DispatchMode::Sequential => Self::Ordered(None, Arc::default()),
+        }
+    }
+}
+
+impl Kook {
+    /// Hand a gateway event over for handling without waiting.
+    ///
+    /// Collectors are fed right away, so a handler waiting for a reply is
+    /// never stuck behind its own queue or slot. The rest happens in arrival
+    /// order on a feeder task, so neither a slow cache or stream nor a full
+    /// `max_in_flight` holds up reading the connection and its heartbeat;
+    /// events wait in memory instead.
+    pub(crate) fn submit(self: &Arc<Self>, event: Event<EventExtra>) {
+        self.collectors.feed(&event);
+        let feeder = self.feeder.get_or_init(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(Self::feed(Arc::downgrade(self), rx));
+            tx
+        });
+        feeder.send(event).ok();
+    }
+
+    /// Schedule submitted events until the `Kook` is dropped.
+    async fn feed(kook: Weak<Self>, mut rx: mpsc::UnboundedReceiver<Event<EventExtra>>) {
+        while let Some(event) = rx.recv().await {
+            match kook.upgrade() {
+                Some(kook) => kook.schedule(event).await,
+                None => return,
+            }
+        }
+    }
+
+    /// Update the caches, presence tracker and event streams, then schedule
+    /// `event` according to the configured `DispatchMode`. Waits for a free
+    /// slot when `max_in_flight` is reached.
+    async fn schedule(self: &Arc<Self>, event: Event<EventExtra>) {
+        let track = async {
+            if let Some(cache) = &self.cache {
+                cache.update(&event).await;
+            }
+            if let Some(presence) = &self.presence {
+                presence.update(&event);
+            }
+            Ok(())
+        };
+        if let Err(e) = catch_panic(track).await {
+            error!(target: KOOK, "failed to update state from event: {}", e);
+        }
+        let previous_message = self
+            .message_cache
+            .as_ref()
+            .and_then(|cache| cache.update(&event));
+        self.subscribers.feed(&event).await;
+        match &self.dispatcher {
+            Dispatcher::Concurrent(semaphore) => {
+                let permit = match semaphore {
+                    Some(s) => Some(s.clone().acquire_owned().await.unwrap()),
+                    None => None,
+                };
+                let khl = self.clone();
+                tokio::spawn(async move {
+                    khl.dispatch(event, previous_message).await;
+                    drop(permit);
+                });
+            }
+            Dispatcher::Ordered(by, queues) => {
+                let key = by.map(|by| by.key(&event)).unwrap_or_default();
+                let mut spawn = None;
+                let queue = queues.entry(key.clone()).or_insert_with(|| {
+                    let (tx, rx) = mpsc::unbounded_channel();
+                    let pending = Arc::new(AtomicUsize::default());
+                    spawn = Some((rx, pending.clone()));
+                    Queue { tx, pending }
+                });
+                queue.pending.fetch_add(1, Ordering::SeqCst);
+                queue.tx.send((event, previous_message)).ok();
+                drop(queue);
+                if let Some((rx, pending)) = spawn {
+                    tokio::spawn(self.clone().run_queue(key, rx, pending));
+                }
+            }
+        }
+    }
+
+    /// Handle events of one key in order, exits once the queue is drained.
+    async fn run_queue(
+        self: Arc<Self>,
+        key: String,
+        mut rx: mpsc::UnboundedReceiver<Queued>,
+        pending: Arc<AtomicUsize>,
+    ) {
+        let guard = match &self.dispatcher {
+            Dispatcher::Ordered(_, queues) => QueueGuard {
+                queues: queues.clone(),
+                key,
+                pending,
+            },
+            Dispatcher::Concurrent(_) => return,
+        };
+        while let Some((event, previous_message)) = rx.recv().await {
+            self.dispatch(event, previous_message).await;
+            if guard.pending.fetch_sub(1, Ordering::SeqCst) == 1
+                && guard
+                    .queues
+                    .remove_if(&guard.key, |_, q| q.pending.load(Ordering::SeqCst) == 0)
+                    .is_some()
+            {
+                break;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn ordered_dispatch_test() {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Default, Clone)]
+    struct Record(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait::async_trait]
+    impl EventHandler for Record {
+        async fn handle(&self, _ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+            // later events finish first if they are not ordered
+            let delay = 30 - event.content.parse::<u64>().unwrap() * 10;
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            self.0.lock().unwrap().push(event.content);
+            Ok(())
+        }
+    }
+
+    let record = Record::default();
+    let config = Config {
+        dispatch: DispatchMode::Ordered {
+            by: OrderKey::Channel,
+        },
+        ..Config::default()
+    };
+    let kook = Kook::new_from_config(config, record.clone()).arc();
+    for i in 0..3 {
+        let event = crate::test::group_message(&i.to_string());
+        kook.submit(event);
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(*record.0.lock().unwrap(), vec!["0", "1", "2"]);
+    match &kook.dispatcher {
+        Dispatcher::Ordered(_, queues) => assert!(queues.is_empty()),
+        Dispatcher::Concurrent(_) => unreachable!(),
+    }
+}
+
+#[tokio::test]
+async fn ordered_wait_for_reply_test() {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Default, Clone)]
+    struct Ask(Arc<Mutex<Option<String>>>);
+
+    #[async_trait::async_trait]
+    impl EventHandler for Ask {
+        async fn handle(&self, ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+            if event.content == "ask" {
+                let reply = ctx
+                    .wait_for_reply(&event.author_id, Duration::from_secs(1))
+                    .await;
+                *self.0.lock().unwrap() = reply.map(|e| e.content);
+            }
+            Ok(())
+        }
+    }
+
+    let ask = Ask::default();
+    let config = Config {
+        dispatch: DispatchMode::Ordered {
+            by: OrderKey::Channel,
+        },
+        ..Config::default()
+    };
+    let kook = Kook::new_from_config(config, ask.clone()).arc();
+    for content in ["ask", "answer"] {
+        let event = crate::test::group_message(content);
+        kook.submit(event);
+        // let the handler of "ask" start waiting before the reply arrives
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(ask.0.lock().unwrap().as_deref(), Some("answer"));
+}
+
+#[tokio::test]
+async fn ordered_panic_test() {
+    use std::time::Duration;
+
+    #[derive(Default, Clone)]
+    struct Count(Arc<AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl EventHandler for Count {
+        async fn handle(&self, _ctx: &Context, _event: Event<EventExtra>) -> HandlerResult {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Err("failed".into())
+        }
+    }
+
+    struct Panics;
+
+    #[async_trait::async_trait]
+    impl ErrorHandler for Panics {
+        async fn on_error(&self, _ctx: &Context, _event: &Event<EventExtra>, error: HandlerError) {
+            if error.to_string() == "failed" {
+                panic!("error handler");
+            }
+        }
+    }
+
+    let count = Count::default();
+    let config = Config {
+        dispatch: DispatchMode::Sequential,
+        ..Config::default()
+    };
+    let kook = Kook::new_from_config(config, count.clone())
+        .with_error_handler(Panics)
+        .arc();
+    for i in 0..2 {
+        kook.submit(crate::test::group_message(&i.to_string()));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(count.0.load(Ordering::SeqCst), 2);
+    match &kook.dispatcher {
+        Dispatcher::Ordered(_, queues) => assert!(queues.is_empty()),
+        Dispatcher::Concurrent(_) => unreachable!(),
+    }
+}
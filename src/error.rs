@@ -14,4 +14,6 @@ pub enum KookError {
     HttpApiCallEmptyResponse,
     #[error("reqwest error:{0}")]
     ReqwestError(#[from] reqwest::Error),
+    #[error("event has no {0} to act on")]
+    MissingEventField(&'static str),
 }
@@ -1,19 +1,27 @@
-use serde::Deserialize;
+use serde::{
+    de::{value::MapDeserializer, Error as DeError},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+use tracing::{debug, warn};
 
 use crate::prelude::*;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event<T> {
-    pub channel_type: String,
+    pub channel_type: EventChannelType,
     #[serde(rename = "type")]
-    pub ty: u8,
+    pub ty: MessageType,
     pub target_id: String,
     pub author_id: String,
     pub msg_id: String,
     pub content: String,
-    pub msg_timestamp: i64,
+    pub msg_timestamp: Timestamp,
     pub nonce: String,
     pub extra: T,
+    #[serde(skip)]
+    pub raw: Option<Arc<JsonValue>>,
 }
 
 impl Event<EventExtra> {
@@ -32,6 +40,7 @@ impl Event<EventExtra> {
                 msg_timestamp: self.msg_timestamp,
                 nonce: self.nonce,
                 extra,
+                raw: self.raw,
             }),
             Err(_) => None,
         }
@@ -44,9 +53,68 @@ impl Event<EventExtra> {
             _ => None,
         }
     }
+
+    pub fn guild_id(&self) -> Option<&str> {
+        match &self.extra {
+            EventExtra::GroupMessage(g) => Some(&g.guild_id),
+            EventExtra::System(SystemExtra::MessageBtnClick(b)) if b.guild_id.is_some() => {
+                b.guild_id.as_deref()
+            }
+            EventExtra::System(_) if self.channel_type == EventChannelType::Group => {
+                Some(&self.target_id)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn channel_id(&self) -> Option<&str> {
+        match &self.extra {
+            EventExtra::GroupMessage(_) => Some(&self.target_id),
+            EventExtra::System(s) => s.channel_id(),
+            EventExtra::PersonMessage(_) => None,
+        }
+    }
+}
+
+impl<T> Event<T> {
+    pub fn map_extra<U, F>(self, f: F) -> Event<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        Event {
+            channel_type: self.channel_type,
+            ty: self.ty,
+            target_id: self.target_id,
+            author_id: self.author_id,
+            msg_id: self.msg_id,
+            content: self.content,
+            msg_timestamp: self.msg_timestamp,
+            nonce: self.nonce,
+            extra: f(self.extra),
+            raw: self.raw,
+        }
+    }
+
+    /// Parse `content` as KMarkdown.
+    pub fn kmarkdown(&self) -> Vec<crate::KMDItem> {
+        crate::kmd_from_str(&self.content)
+    }
+
+    /// Decode `content` of a card message.
+    pub fn cards(&self) -> KookResult<crate::card::Cards> {
+        crate::card::cards_decode(&self.content)
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EventChannelType {
+    Group,
+    Person,
+    Broadcast,
+}
+
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum EventExtra {
     System(SystemExtra),
@@ -54,173 +122,410 @@ pub enum EventExtra {
     PersonMessage(PersonMessageExtra),
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(tag = "type", content = "body", rename_all = "snake_case")]
+impl<'de> Deserialize<'de> for EventExtra {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = JsonValue::deserialize(deserializer)?;
+        if value.get("type").is_some_and(JsonValue::is_string) {
+            <SystemExtra as Deserialize>::deserialize(value)
+                .map(EventExtra::System)
+                .map_err(DeError::custom)
+        } else if value.get("guild_id").is_some() {
+            de_with_path(&value)
+                .map(EventExtra::GroupMessage)
+                .map_err(DeError::custom)
+        } else {
+            de_with_path(&value)
+                .map(EventExtra::PersonMessage)
+                .map_err(DeError::custom)
+        }
+    }
+}
+
+/// Deserialize `value`, prefixing the error with the path of the field that failed.
+pub(crate) fn de_with_path<'de, T>(value: &'de JsonValue) -> Result<T, String>
+where
+    T: Deserialize<'de>,
+{
+    serde_path_to_error::deserialize(value).map_err(|e| format!("{}: {}", e.path(), e.inner()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    remote = "Self",
+    tag = "type",
+    content = "body",
+    rename_all = "snake_case"
+)]
 pub enum SystemExtra {
     // Channel
-    AddedReaction {
-        channel_id: String,
-        emoji: Emoji,
-        user_id: String,
-        msg_id: String,
-    },
-    DeletedReaction {
-        channel_id: String,
-        emoji: Emoji,
-        user_id: String,
-        msg_id: String,
-    },
-    UpdatedMessage {
-        channel_id: String,
-        content: String,
-        mention: Vec<String>,
-        mention_all: bool,
-        mention_here: bool,
-        mention_roles: Vec<i32>,
-        updated_at: i64,
-        msg_id: String,
-    },
-    DeletedMessage {
-        channel_id: String,
-        msg_id: String,
-    },
+    AddedReaction(Reaction),
+    DeletedReaction(Reaction),
+    UpdatedMessage(UpdatedMessage),
+    DeletedMessage(DeletedMessage),
     AddedChannel(Channel),
     UpdatedChannel(Channel),
-    DeletedChannel {
-        id: String,
-        deleted_at: i64,
-    },
-    PinnedMessage {
-        channel_id: String,
-        operator_id: String,
-        msg_id: String,
-    },
-    UnpinnedMessage {
-        channel_id: String,
-        operator_id: String,
-        msg_id: String,
-    },
+    DeletedChannel(DeletedChannel),
+    PinnedMessage(PinnedMessage),
+    UnpinnedMessage(PinnedMessage),
     // private
-    UpdatedPrivateMessage {
-        msg_id: String,
-        author_id: String,
-        target_id: String,
-        content: String,
-        chat_code: String,
-        updated_at: i64,
-    },
-    DeletedPrivateMessage {
-        msg_id: String,
-        author_id: String,
-        target_id: String,
-        chat_code: String,
-        deleted_at: i64,
-    },
-    PrivateAddedReaction {
-        msg_id: String,
-        user_id: String,
-        chat_code: String,
-        emoji: Emoji,
-    },
-    PrivateDeletedReaction {
-        msg_id: String,
-        user_id: String,
-        chat_code: String,
-        emoji: Emoji,
-    },
+    UpdatedPrivateMessage(UpdatedPrivateMessage),
+    DeletedPrivateMessage(DeletedPrivateMessage),
+    PrivateAddedReaction(PrivateReaction),
+    PrivateDeletedReaction(PrivateReaction),
     // guild member
-    JoinedGuild {
-        user_id: String,
-        joined_at: i64,
-    },
-    ExitedGuild {
-        user_id: String,
-        exited_at: i64,
-    },
-    UpdateGuildMember {
-        user_id: String,
-        nickname: String,
-    },
-    GuildMemberOnline {
-        user_id: String,
-        event_time: i64,
-        guilds: Vec<String>,
-    },
-    GuildMemberOffline {
-        user_id: String,
-        event_time: i64,
-        guilds: Vec<String>,
-    },
+    JoinedGuild(JoinedGuild),
+    ExitedGuild(ExitedGuild),
+    UpdateGuildMember(UpdateGuildMember),
+    GuildMemberOnline(GuildMemberPresence),
+    GuildMemberOffline(GuildMemberPresence),
     // role
     AddedRole(Role),
     DeletedRole(Role),
     UpdatedRole(Role),
     // guild
-    UpdateGuild {
-        // bad
-        id: String,
-        name: String,
-        user_id: String,
-        icon: String,
-        notify_type: i64,
-        region: String,
-        enable_open: i64,
-        open_id: i64,
-        default_channel_id: String,
-        welcome_channel_id: String,
-    },
-    DeletedGuild {
-        id: String,
-        name: String,
-        user_id: String,
-        icon: String,
-        notify_type: i64,
-        region: String,
-        enable_open: i64,
-        open_id: i64,
-        default_channel_id: String,
-        welcome_channel_id: String,
-    },
-    AddedBlockList {
-        operator_id: String,
-        remark: String,
-        user_id: String,
-    },
-    DeletedBlockList {
-        operator_id: String,
-        user_id: String,
-    },
+    UpdateGuild(GuildInfo),
+    DeletedGuild(GuildInfo),
+    AddedBlockList(AddedBlockList),
+    DeletedBlockList(DeletedBlockList),
     // user
-    JoinedChannel {
-        user_id: String,
-        channel_id: String,
-        joined_at: i64,
-    },
-    ExitedChannel {
-        user_id: String,
-        channel_id: String,
-        exited_at: String,
-    },
-    UserUpdated {
-        user_id: String,
-        username: String,
-        avatar: String,
-    },
-    SelfJoinedGuild {
-        guild_id: String,
-    },
-    SelfExitedGuild {
-        guild_id: String,
-    },
-    MessageBtnClick {
-        msg_id: String,
-        user_id: String,
-        value: String,
-        target_id: String,
-        user_info: User,
+    JoinedChannel(JoinedChannel),
+    ExitedChannel(ExitedChannel),
+    UserUpdated(UserUpdated),
+    SelfJoinedGuild(SelfGuild),
+    SelfExitedGuild(SelfGuild),
+    MessageBtnClick(MessageBtnClick),
+    /// An event this version does not know, or one whose body failed to parse.
+    #[serde(skip)]
+    Unknown {
+        ty: String,
+        body: JsonValue,
     },
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reaction {
+    pub channel_id: String,
+    pub emoji: Emoji,
+    pub user_id: String,
+    pub msg_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatedMessage {
+    pub channel_id: String,
+    pub content: String,
+    pub mention: Vec<String>,
+    pub mention_all: bool,
+    pub mention_here: bool,
+    pub mention_roles: Vec<i32>,
+    pub updated_at: Timestamp,
+    pub msg_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedMessage {
+    pub channel_id: String,
+    pub msg_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedChannel {
+    pub id: String,
+    pub deleted_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedMessage {
+    pub channel_id: String,
+    pub operator_id: String,
+    pub msg_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatedPrivateMessage {
+    pub msg_id: String,
+    pub author_id: String,
+    pub target_id: String,
+    pub content: String,
+    pub chat_code: String,
+    pub updated_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedPrivateMessage {
+    pub msg_id: String,
+    pub author_id: String,
+    pub target_id: String,
+    pub chat_code: String,
+    pub deleted_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivateReaction {
+    pub msg_id: String,
+    pub user_id: String,
+    pub chat_code: String,
+    pub emoji: Emoji,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinedGuild {
+    pub user_id: String,
+    pub joined_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitedGuild {
+    pub user_id: String,
+    pub exited_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateGuildMember {
+    pub user_id: String,
+    pub nickname: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuildMemberPresence {
+    pub user_id: String,
+    pub event_time: Timestamp,
+    pub guilds: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuildInfo {
+    // bad
+    pub id: String,
+    pub name: String,
+    pub user_id: String,
+    pub icon: String,
+    pub notify_type: i64,
+    pub region: String,
+    pub enable_open: i64,
+    pub open_id: i64,
+    pub default_channel_id: String,
+    pub welcome_channel_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddedBlockList {
+    pub operator_id: String,
+    pub remark: String,
+    pub user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedBlockList {
+    pub operator_id: String,
+    pub user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinedChannel {
+    pub user_id: String,
+    pub channel_id: String,
+    pub joined_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitedChannel {
+    pub user_id: String,
+    pub channel_id: String,
+    pub exited_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserUpdated {
+    pub user_id: String,
+    pub username: String,
+    pub avatar: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfGuild {
+    pub guild_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageBtnClick {
+    pub msg_id: String,
+    pub user_id: String,
+    pub value: String,
+    /// The channel of the clicked message, or the user for direct messages.
+    pub target_id: String,
+    pub user_info: User,
+    /// Where the clicked message is, the event itself always comes as `PERSON`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_type: Option<EventChannelType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guild_id: Option<String>,
+}
+
+/// Deserialize a system event, errors start with the path of the failed
+/// field like those of `de_with_path`.
+fn de_system_extra(ty: &str, body: JsonValue) -> Result<SystemExtra, String> {
+    // `type` goes first so the body is deserialized straight into the
+    // variant's payload instead of being buffered, which loses the path
+    let fields = [("type", JsonValue::String(ty.to_owned())), ("body", body)];
+    let map = MapDeserializer::<_, serde_json::Error>::new(fields.into_iter());
+    let mut track = serde_path_to_error::Track::new();
+    SystemExtra::deserialize(serde_path_to_error::Deserializer::new(map, &mut track))
+        .map_err(|e| format!("{}: {}", track.path(), e))
+}
+
+impl<'de> Deserialize<'de> for SystemExtra {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct RawSystemExtra {
+            #[serde(rename = "type")]
+            ty: String,
+            #[serde(default)]
+            body: JsonValue,
+        }
+
+        let raw = RawSystemExtra::deserialize(deserializer)?;
+        match de_system_extra(&raw.ty, raw.body.clone()) {
+            Ok(extra) => Ok(extra),
+            Err(e) => {
+                if e.starts_with("type:") {
+                    debug!(target: KOOK, "received unknown system event {}", raw.ty);
+                } else {
+                    warn!(target: KOOK, "parse system event {} failed at {}", raw.ty, e);
+                }
+                Ok(SystemExtra::Unknown {
+                    ty: raw.ty,
+                    body: raw.body,
+                })
+            }
+        }
+    }
+}
+
+impl SystemExtra {
+    /// The `type` string KOOK sends for this event.
+    pub fn ty(&self) -> &str {
+        match self {
+            SystemExtra::AddedReaction(_) => "added_reaction",
+            SystemExtra::DeletedReaction(_) => "deleted_reaction",
+            SystemExtra::UpdatedMessage(_) => "updated_message",
+            SystemExtra::DeletedMessage(_) => "deleted_message",
+            SystemExtra::AddedChannel(_) => "added_channel",
+            SystemExtra::UpdatedChannel(_) => "updated_channel",
+            SystemExtra::DeletedChannel(_) => "deleted_channel",
+            SystemExtra::PinnedMessage(_) => "pinned_message",
+            SystemExtra::UnpinnedMessage(_) => "unpinned_message",
+            SystemExtra::UpdatedPrivateMessage(_) => "updated_private_message",
+            SystemExtra::DeletedPrivateMessage(_) => "deleted_private_message",
+            SystemExtra::PrivateAddedReaction(_) => "private_added_reaction",
+            SystemExtra::PrivateDeletedReaction(_) => "private_deleted_reaction",
+            SystemExtra::JoinedGuild(_) => "joined_guild",
+            SystemExtra::ExitedGuild(_) => "exited_guild",
+            SystemExtra::UpdateGuildMember(_) => "update_guild_member",
+            SystemExtra::GuildMemberOnline(_) => "guild_member_online",
+            SystemExtra::GuildMemberOffline(_) => "guild_member_offline",
+            SystemExtra::AddedRole(_) => "added_role",
+            SystemExtra::DeletedRole(_) => "deleted_role",
+            SystemExtra::UpdatedRole(_) => "updated_role",
+            SystemExtra::UpdateGuild(_) => "update_guild",
+            SystemExtra::DeletedGuild(_) => "deleted_guild",
+            SystemExtra::AddedBlockList(_) => "added_block_list",
+            SystemExtra::DeletedBlockList(_) => "deleted_block_list",
+            SystemExtra::JoinedChannel(_) => "joined_channel",
+            SystemExtra::ExitedChannel(_) => "exited_channel",
+            SystemExtra::UserUpdated(_) => "user_updated",
+            SystemExtra::SelfJoinedGuild(_) => "self_joined_guild",
+            SystemExtra::SelfExitedGuild(_) => "self_exited_guild",
+            SystemExtra::MessageBtnClick(_) => "message_btn_click",
+            SystemExtra::Unknown { ty, .. } => ty,
+        }
+    }
+
+    /// The user who triggered this event.
+    pub fn user_id(&self) -> Option<&str> {
+        match self {
+            SystemExtra::AddedReaction(r) | SystemExtra::DeletedReaction(r) => Some(&r.user_id),
+            SystemExtra::PinnedMessage(p) | SystemExtra::UnpinnedMessage(p) => Some(&p.operator_id),
+            SystemExtra::UpdatedPrivateMessage(m) => Some(&m.author_id),
+            SystemExtra::DeletedPrivateMessage(m) => Some(&m.author_id),
+            SystemExtra::PrivateAddedReaction(r) | SystemExtra::PrivateDeletedReaction(r) => {
+                Some(&r.user_id)
+            }
+            SystemExtra::JoinedGuild(m) => Some(&m.user_id),
+            SystemExtra::ExitedGuild(m) => Some(&m.user_id),
+            SystemExtra::UpdateGuildMember(m) => Some(&m.user_id),
+            SystemExtra::GuildMemberOnline(p) | SystemExtra::GuildMemberOffline(p) => {
+                Some(&p.user_id)
+            }
+            SystemExtra::AddedBlockList(b) => Some(&b.operator_id),
+            SystemExtra::DeletedBlockList(b) => Some(&b.operator_id),
+            SystemExtra::JoinedChannel(c) => Some(&c.user_id),
+            SystemExtra::ExitedChannel(c) => Some(&c.user_id),
+            SystemExtra::UserUpdated(u) => Some(&u.user_id),
+            SystemExtra::MessageBtnClick(b) => Some(&b.user_id),
+            _ => None,
+        }
+    }
+
+    /// The message this event refers to.
+    pub fn msg_id(&self) -> Option<&str> {
+        match self {
+            SystemExtra::AddedReaction(r) | SystemExtra::DeletedReaction(r) => Some(&r.msg_id),
+            SystemExtra::UpdatedMessage(m) => Some(&m.msg_id),
+            SystemExtra::DeletedMessage(m) => Some(&m.msg_id),
+            SystemExtra::PinnedMessage(p) | SystemExtra::UnpinnedMessage(p) => Some(&p.msg_id),
+            SystemExtra::UpdatedPrivateMessage(m) => Some(&m.msg_id),
+            SystemExtra::DeletedPrivateMessage(m) => Some(&m.msg_id),
+            SystemExtra::PrivateAddedReaction(r) | SystemExtra::PrivateDeletedReaction(r) => {
+                Some(&r.msg_id)
+            }
+            SystemExtra::MessageBtnClick(b) => Some(&b.msg_id),
+            _ => None,
+        }
+    }
+
+    pub fn channel_id(&self) -> Option<&str> {
+        match self {
+            SystemExtra::AddedReaction(r) | SystemExtra::DeletedReaction(r) => Some(&r.channel_id),
+            SystemExtra::UpdatedMessage(m) => Some(&m.channel_id),
+            SystemExtra::DeletedMessage(m) => Some(&m.channel_id),
+            SystemExtra::AddedChannel(c) | SystemExtra::UpdatedChannel(c) => Some(&c.id),
+            SystemExtra::DeletedChannel(c) => Some(&c.id),
+            SystemExtra::PinnedMessage(p) | SystemExtra::UnpinnedMessage(p) => Some(&p.channel_id),
+            SystemExtra::JoinedChannel(c) => Some(&c.channel_id),
+            SystemExtra::ExitedChannel(c) => Some(&c.channel_id),
+            SystemExtra::MessageBtnClick(b) if b.channel_type != Some(EventChannelType::Person) => {
+                Some(&b.target_id)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for SystemExtra {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap;
+        match self {
+            SystemExtra::Unknown { ty, body } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("type", ty)?;
+                map.serialize_entry("body", body)?;
+                map.end()
+            }
+            extra => SystemExtra::serialize(extra, serializer),
+        }
+    }
+}
+
 impl TryFrom<EventExtra> for SystemExtra {
     type Error = ();
 
@@ -232,10 +537,10 @@ impl TryFrom<EventExtra> for SystemExtra {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupMessageExtra {
     #[serde(rename = "type")]
-    pub ty: i32,
+    pub ty: MessageType,
     pub guild_id: String,
     pub channel_name: String,
     pub mention: Vec<String>,
@@ -243,6 +548,18 @@ pub struct GroupMessageExtra {
     pub mention_roles: Vec<i32>,
     pub mention_here: bool,
     pub author: User,
+    #[serde(default)]
+    pub nav_channels: Vec<String>,
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub quote: Option<Quote>,
+    #[serde(default)]
+    pub attachments: Option<Attachments>,
+    #[serde(default)]
+    pub kmarkdown: Option<MessageKMarkdown>,
+    #[serde(default)]
+    pub last_msg_content: String,
 }
 
 impl TryFrom<EventExtra> for GroupMessageExtra {
@@ -256,15 +573,25 @@ impl TryFrom<EventExtra> for GroupMessageExtra {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersonMessageExtra {
     #[serde(rename = "type")]
-    pub ty: i32,
+    pub ty: MessageType,
     pub author: User,
     pub mention: Vec<String>,
     pub mention_all: bool,
     pub mention_roles: Vec<i32>,
     pub mention_here: bool,
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub quote: Option<Quote>,
+    #[serde(default)]
+    pub attachments: Option<Attachments>,
+    #[serde(default)]
+    pub kmarkdown: Option<MessageKMarkdown>,
+    #[serde(default)]
+    pub last_msg_content: String,
 }
 
 impl TryFrom<EventExtra> for PersonMessageExtra {
@@ -294,3 +621,169 @@ fn extra_de_test() {
     let e: EventExtra = serde_json::from_str(s).unwrap();
     println!("{:#?}", e);
 }
+
+#[test]
+fn enum_round_trip_test() {
+    assert_eq!(
+        serde_json::from_str::<EventChannelType>(r#""BROADCAST""#).unwrap(),
+        EventChannelType::Broadcast
+    );
+    assert_eq!(
+        serde_json::to_string(&EventChannelType::Group).unwrap(),
+        r#""GROUP""#
+    );
+    assert_eq!(
+        serde_json::from_str::<MessageType>("9").unwrap(),
+        MessageType::KMarkdown
+    );
+    assert_eq!(
+        serde_json::from_str::<MessageType>("42").unwrap(),
+        MessageType::Unknown(42)
+    );
+    assert_eq!(
+        serde_json::to_string(&MessageType::Unknown(42)).unwrap(),
+        "42"
+    );
+    assert_eq!(
+        serde_json::from_str::<ChannelKind>("2").unwrap(),
+        ChannelKind::Voice
+    );
+    assert_eq!(serde_json::to_string(&ChannelKind::Category).unwrap(), "0");
+    assert_eq!(
+        serde_json::from_str::<ChannelKind>("7").unwrap(),
+        ChannelKind::Unknown(7)
+    );
+    assert_eq!(
+        serde_json::to_string(&ChannelKind::Unknown(7)).unwrap(),
+        "7"
+    );
+}
+
+#[test]
+fn unknown_extra_de_test() {
+    let s = r#"{
+        "type": "some_new_event",
+        "body": {
+          "foo": 1
+        }
+      }"#;
+    match serde_json::from_str::<EventExtra>(s).unwrap() {
+        EventExtra::System(SystemExtra::Unknown { ty, body }) => {
+            assert_eq!(ty, "some_new_event");
+            assert_eq!(body["foo"], 1);
+        }
+        e => panic!("unexpected extra: {:?}", e),
+    }
+    let s = r#"{
+        "type": "deleted_message",
+        "body": {
+          "channel_id": 123,
+          "msg_id": "xxx"
+        }
+      }"#;
+    assert!(matches!(
+        serde_json::from_str::<EventExtra>(s).unwrap(),
+        EventExtra::System(SystemExtra::Unknown { .. })
+    ));
+    let body = serde_json::json!({ "channel_id": 123, "msg_id": "xxx" });
+    let e = de_system_extra("deleted_message", body).unwrap_err();
+    assert!(
+        e.starts_with("body.channel_id: invalid type: integer"),
+        "{}",
+        e
+    );
+    let e = de_system_extra("deleted_message", serde_json::json!({})).unwrap_err();
+    assert!(e.starts_with("body: missing field"), "{}", e);
+    let e = de_system_extra("some_new_event", serde_json::json!({})).unwrap_err();
+    assert!(e.starts_with("type:"), "{}", e);
+}
+
+#[test]
+fn group_message_de_test() {
+    let s = r#"{
+        "channel_type": "GROUP",
+        "type": 9,
+        "target_id": "xxx",
+        "author_id": "xxx",
+        "content": "(met)123(met) **hello** [link](https://kookapp.cn)",
+        "msg_id": "xxx",
+        "msg_timestamp": 1607679133000,
+        "nonce": "",
+        "extra": {
+          "type": 9,
+          "guild_id": "xxx",
+          "channel_name": "general",
+          "mention": ["123"],
+          "mention_all": false,
+          "mention_roles": [],
+          "mention_here": false,
+          "nav_channels": [],
+          "code": "",
+          "author": {
+            "id": "xxx",
+            "username": "name",
+            "nickname": "name",
+            "identify_num": "1234",
+            "online": true,
+            "bot": false,
+            "status": 1,
+            "avatar": "https://xxx.com/assets/avatar.png",
+            "vip_avatar": "https://xxx.com/assets/avatar.png",
+            "roles": []
+          },
+          "quote": {
+            "id": "yyy",
+            "type": 1,
+            "content": "quoted",
+            "create_at": 1607679130000,
+            "author": {
+              "id": "yyy",
+              "username": "other",
+              "nickname": "other",
+              "identify_num": "4321",
+              "online": false,
+              "bot": false,
+              "status": 0,
+              "avatar": "",
+              "vip_avatar": "",
+              "roles": []
+            }
+          },
+          "attachments": {
+            "type": "image",
+            "url": "https://xxx.com/assets/image.png",
+            "name": "image.png",
+            "size": 123456
+          },
+          "kmarkdown": {
+            "raw_content": "@name hello link",
+            "mention_part": [
+              { "id": "123", "username": "name", "full_name": "name#1234", "avatar": "" }
+            ],
+            "mention_role_part": []
+          },
+          "last_msg_content": "hello"
+        }
+      }"#;
+    let event: Event<EventExtra> = serde_json::from_str(s).unwrap();
+    assert!(matches!(event.kmarkdown()[0], crate::KMDItem::Mention(_)));
+    let event: Event<GroupMessageExtra> = event.down_case().unwrap();
+    assert_eq!(event.ty, MessageType::KMarkdown);
+    assert_eq!(event.extra.quote.unwrap().content, "quoted");
+    assert_eq!(event.extra.attachments.unwrap().size, 123456);
+    assert_eq!(event.extra.kmarkdown.unwrap().mention_part[0].id, "123");
+}
+
+#[test]
+fn extra_ser_test() {
+    let s = r#"{"type":"some_new_event","body":{"foo":1}}"#;
+    let e: EventExtra = serde_json::from_str(s).unwrap();
+    assert_eq!(serde_json::to_string(&e).unwrap(), s);
+    let s = r#"{"type":"deleted_message","body":{"channel_id":"xxx","msg_id":"yyy"}}"#;
+    let e: EventExtra = serde_json::from_str(s).unwrap();
+    assert_eq!(serde_json::to_string(&e).unwrap(), s);
+    match e {
+        EventExtra::System(s) => assert_eq!(s.ty(), "deleted_message"),
+        _ => panic!("should be system extra"),
+    }
+}
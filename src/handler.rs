@@ -1,39 +1,384 @@
 use crate::prelude::*;
 use async_trait::async_trait;
+use futures_util::FutureExt;
+use std::{future::Future, panic::AssertUnwindSafe};
+use thiserror::Error;
+use tracing::error;
+
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+pub type HandlerResult = Result<(), HandlerError>;
+
+/// Reported to the `ErrorHandler` when a handler panics.
+#[derive(Debug, Error)]
+#[error("handler panicked: {0}")]
+pub struct HandlerPanic(pub String);
+
+/// Run a handler future, turning a panic into a `HandlerPanic` error.
+pub(crate) async fn catch_panic<F>(fut: F) -> HandlerResult
+where
+    F: Future<Output = HandlerResult>,
+{
+    match AssertUnwindSafe(fut).catch_unwind().await {
+        Ok(r) => r,
+        Err(panic) => {
+            let msg = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Err(Box::new(HandlerPanic(msg)))
+        }
+    }
+}
+
+/// Returned by a `Router` when several of its handlers fail, `Kook` passes
+/// each of them to the `ErrorHandler`.
+#[derive(Debug, Error)]
+#[error("{} handlers failed", .0.len())]
+pub struct HandlerErrors(pub Vec<HandlerError>);
+
+/// Called with the originating event when a handler or middleware returns
+/// an error or panics.
+#[async_trait]
+pub trait ErrorHandler: Sync + Send {
+    async fn on_error(&self, ctx: &Context, event: &Event<EventExtra>, error: HandlerError);
+}
+
+/// The default `ErrorHandler`, logs the error.
+pub struct LogError;
+
+#[async_trait]
+impl ErrorHandler for LogError {
+    async fn on_error(&self, _ctx: &Context, event: &Event<EventExtra>, error: HandlerError) {
+        error!(target: KOOK, "handling event {} failed: {}", event.msg_id, error);
+    }
+}
 
 #[async_trait]
 pub trait EventHandler: Sync + Send {
-    async fn _handle(&self, khl: &Kook, event: Event<EventExtra>) {
-        self.handle(khl, event.clone()).await;
+    async fn _handle(&self, ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+        self.handle(ctx, event.clone()).await?;
         match &event.extra {
             EventExtra::System(_) => {
-                self.handle_system_event(khl, event.down_case().unwrap())
-                    .await
+                let event: Event<SystemExtra> = event.down_case().unwrap();
+                self.handle_system_event(ctx, event.clone()).await?;
+                self._handle_system_extra(ctx, event).await
             }
             EventExtra::GroupMessage(_) => {
-                self.handle_group_message_event(khl, event.down_case().unwrap())
+                self.handle_group_message_event(ctx, event.down_case().unwrap())
                     .await
             }
             EventExtra::PersonMessage(_) => {
-                self.handle_person_message_event(khl, event.down_case().unwrap())
+                self.handle_person_message_event(ctx, event.down_case().unwrap())
                     .await
             }
         }
     }
 
-    async fn handle(&self, _khl: &Kook, _event: Event<EventExtra>) {}
-    async fn handle_system_event(&self, _khl: &Kook, _event: Event<SystemExtra>) {}
-    async fn handle_group_message_event(&self, _khl: &Kook, _event: Event<GroupMessageExtra>) {}
-    async fn handle_person_message_event(&self, _khl: &Kook, _event: Event<PersonMessageExtra>) {}
+    async fn _handle_system_extra(
+        &self,
+        ctx: &Context,
+        event: Event<SystemExtra>,
+    ) -> HandlerResult {
+        let extra = event.extra.clone();
+        match extra {
+            SystemExtra::AddedReaction(r) => {
+                self.on_reaction_added(ctx, event.map_extra(|_| r)).await
+            }
+            SystemExtra::DeletedReaction(r) => {
+                self.on_reaction_deleted(ctx, event.map_extra(|_| r)).await
+            }
+            SystemExtra::UpdatedMessage(m) => {
+                self.on_message_updated(ctx, event.map_extra(|_| m)).await
+            }
+            SystemExtra::DeletedMessage(m) => {
+                self.on_message_deleted(ctx, event.map_extra(|_| m)).await
+            }
+            SystemExtra::AddedChannel(c) => {
+                self.on_channel_added(ctx, event.map_extra(|_| c)).await
+            }
+            SystemExtra::UpdatedChannel(c) => {
+                self.on_channel_updated(ctx, event.map_extra(|_| c)).await
+            }
+            SystemExtra::DeletedChannel(c) => {
+                self.on_channel_deleted(ctx, event.map_extra(|_| c)).await
+            }
+            SystemExtra::PinnedMessage(p) => {
+                self.on_message_pinned(ctx, event.map_extra(|_| p)).await
+            }
+            SystemExtra::UnpinnedMessage(p) => {
+                self.on_message_unpinned(ctx, event.map_extra(|_| p)).await
+            }
+            SystemExtra::UpdatedPrivateMessage(m) => {
+                self.on_private_message_updated(ctx, event.map_extra(|_| m))
+                    .await
+            }
+            SystemExtra::DeletedPrivateMessage(m) => {
+                self.on_private_message_deleted(ctx, event.map_extra(|_| m))
+                    .await
+            }
+            SystemExtra::PrivateAddedReaction(r) => {
+                self.on_private_reaction_added(ctx, event.map_extra(|_| r))
+                    .await
+            }
+            SystemExtra::PrivateDeletedReaction(r) => {
+                self.on_private_reaction_deleted(ctx, event.map_extra(|_| r))
+                    .await
+            }
+            SystemExtra::JoinedGuild(m) => self.on_member_joined(ctx, event.map_extra(|_| m)).await,
+            SystemExtra::ExitedGuild(m) => self.on_member_exited(ctx, event.map_extra(|_| m)).await,
+            SystemExtra::UpdateGuildMember(m) => {
+                self.on_member_updated(ctx, event.map_extra(|_| m)).await
+            }
+            SystemExtra::GuildMemberOnline(p) => {
+                self.on_member_online(ctx, event.map_extra(|_| p)).await
+            }
+            SystemExtra::GuildMemberOffline(p) => {
+                self.on_member_offline(ctx, event.map_extra(|_| p)).await
+            }
+            SystemExtra::AddedRole(r) => self.on_role_added(ctx, event.map_extra(|_| r)).await,
+            SystemExtra::DeletedRole(r) => self.on_role_deleted(ctx, event.map_extra(|_| r)).await,
+            SystemExtra::UpdatedRole(r) => self.on_role_updated(ctx, event.map_extra(|_| r)).await,
+            SystemExtra::UpdateGuild(g) => self.on_guild_updated(ctx, event.map_extra(|_| g)).await,
+            SystemExtra::DeletedGuild(g) => {
+                self.on_guild_deleted(ctx, event.map_extra(|_| g)).await
+            }
+            SystemExtra::AddedBlockList(b) => {
+                self.on_block_list_added(ctx, event.map_extra(|_| b)).await
+            }
+            SystemExtra::DeletedBlockList(b) => {
+                self.on_block_list_deleted(ctx, event.map_extra(|_| b))
+                    .await
+            }
+            SystemExtra::JoinedChannel(c) => {
+                self.on_channel_joined(ctx, event.map_extra(|_| c)).await
+            }
+            SystemExtra::ExitedChannel(c) => {
+                self.on_channel_exited(ctx, event.map_extra(|_| c)).await
+            }
+            SystemExtra::UserUpdated(u) => self.on_user_updated(ctx, event.map_extra(|_| u)).await,
+            SystemExtra::SelfJoinedGuild(g) => {
+                self.on_self_joined_guild(ctx, event.map_extra(|_| g)).await
+            }
+            SystemExtra::SelfExitedGuild(g) => {
+                self.on_self_exited_guild(ctx, event.map_extra(|_| g)).await
+            }
+            SystemExtra::MessageBtnClick(b) => {
+                self.on_button_click(ctx, event.map_extra(|_| b)).await
+            }
+            SystemExtra::Unknown { .. } => Ok(()),
+        }
+    }
+
+    async fn handle(&self, _ctx: &Context, _event: Event<EventExtra>) -> HandlerResult {
+        Ok(())
+    }
+    async fn handle_system_event(
+        &self,
+        _ctx: &Context,
+        _event: Event<SystemExtra>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn handle_group_message_event(
+        &self,
+        _ctx: &Context,
+        _event: Event<GroupMessageExtra>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn handle_person_message_event(
+        &self,
+        _ctx: &Context,
+        _event: Event<PersonMessageExtra>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+
+    // channel
+    async fn on_reaction_added(&self, _ctx: &Context, _event: Event<Reaction>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_reaction_deleted(&self, _ctx: &Context, _event: Event<Reaction>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_message_updated(
+        &self,
+        _ctx: &Context,
+        _event: Event<UpdatedMessage>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_message_deleted(
+        &self,
+        _ctx: &Context,
+        _event: Event<DeletedMessage>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_channel_added(&self, _ctx: &Context, _event: Event<Channel>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_channel_updated(&self, _ctx: &Context, _event: Event<Channel>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_channel_deleted(
+        &self,
+        _ctx: &Context,
+        _event: Event<DeletedChannel>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_message_pinned(
+        &self,
+        _ctx: &Context,
+        _event: Event<PinnedMessage>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_message_unpinned(
+        &self,
+        _ctx: &Context,
+        _event: Event<PinnedMessage>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    // private
+    async fn on_private_message_updated(
+        &self,
+        _ctx: &Context,
+        _event: Event<UpdatedPrivateMessage>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_private_message_deleted(
+        &self,
+        _ctx: &Context,
+        _event: Event<DeletedPrivateMessage>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_private_reaction_added(
+        &self,
+        _ctx: &Context,
+        _event: Event<PrivateReaction>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_private_reaction_deleted(
+        &self,
+        _ctx: &Context,
+        _event: Event<PrivateReaction>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    // guild member
+    async fn on_member_joined(&self, _ctx: &Context, _event: Event<JoinedGuild>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_member_exited(&self, _ctx: &Context, _event: Event<ExitedGuild>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_member_updated(
+        &self,
+        _ctx: &Context,
+        _event: Event<UpdateGuildMember>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_member_online(
+        &self,
+        _ctx: &Context,
+        _event: Event<GuildMemberPresence>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_member_offline(
+        &self,
+        _ctx: &Context,
+        _event: Event<GuildMemberPresence>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    // role
+    async fn on_role_added(&self, _ctx: &Context, _event: Event<Role>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_role_deleted(&self, _ctx: &Context, _event: Event<Role>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_role_updated(&self, _ctx: &Context, _event: Event<Role>) -> HandlerResult {
+        Ok(())
+    }
+    // guild
+    async fn on_guild_updated(&self, _ctx: &Context, _event: Event<GuildInfo>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_guild_deleted(&self, _ctx: &Context, _event: Event<GuildInfo>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_block_list_added(
+        &self,
+        _ctx: &Context,
+        _event: Event<AddedBlockList>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_block_list_deleted(
+        &self,
+        _ctx: &Context,
+        _event: Event<DeletedBlockList>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    // user
+    async fn on_channel_joined(
+        &self,
+        _ctx: &Context,
+        _event: Event<JoinedChannel>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_channel_exited(
+        &self,
+        _ctx: &Context,
+        _event: Event<ExitedChannel>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_user_updated(&self, _ctx: &Context, _event: Event<UserUpdated>) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_self_joined_guild(
+        &self,
+        _ctx: &Context,
+        _event: Event<SelfGuild>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_self_exited_guild(
+        &self,
+        _ctx: &Context,
+        _event: Event<SelfGuild>,
+    ) -> HandlerResult {
+        Ok(())
+    }
+    async fn on_button_click(
+        &self,
+        _ctx: &Context,
+        _event: Event<MessageBtnClick>,
+    ) -> HandlerResult {
+        Ok(())
+    }
 }
 
 impl EventHandler for tokio::sync::broadcast::Sender<Event<EventExtra>> {
     fn _handle<'life0, 'life1, 'async_trait>(
         &'life0 self,
-        _: &'life1 Kook,
+        _: &'life1 Context,
         event: Event<EventExtra>,
     ) -> core::pin::Pin<
-        Box<dyn core::future::Future<Output = ()> + core::marker::Send + 'async_trait>,
+        Box<dyn core::future::Future<Output = HandlerResult> + core::marker::Send + 'async_trait>,
     >
     where
         'life0: 'async_trait,
@@ -42,6 +387,7 @@ impl EventHandler for tokio::sync::broadcast::Sender<Event<EventExtra>> {
     {
         Box::pin(async move {
             self.send(event).ok();
+            Ok(())
         })
     }
 }
@@ -49,10 +395,10 @@ impl EventHandler for tokio::sync::broadcast::Sender<Event<EventExtra>> {
 impl EventHandler for tokio::sync::mpsc::Sender<Event<EventExtra>> {
     fn _handle<'life0, 'life1, 'async_trait>(
         &'life0 self,
-        _: &'life1 Kook,
+        _: &'life1 Context,
         event: Event<EventExtra>,
     ) -> core::pin::Pin<
-        Box<dyn core::future::Future<Output = ()> + core::marker::Send + 'async_trait>,
+        Box<dyn core::future::Future<Output = HandlerResult> + core::marker::Send + 'async_trait>,
     >
     where
         'life0: 'async_trait,
@@ -61,6 +407,7 @@ impl EventHandler for tokio::sync::mpsc::Sender<Event<EventExtra>> {
     {
         Box::pin(async move {
             self.send(event).await.ok();
+            Ok(())
         })
     }
 }
@@ -68,10 +415,10 @@ impl EventHandler for tokio::sync::mpsc::Sender<Event<EventExtra>> {
 impl EventHandler for tokio::sync::mpsc::UnboundedSender<Event<EventExtra>> {
     fn _handle<'life0, 'life1, 'async_trait>(
         &'life0 self,
-        _: &'life1 Kook,
+        _: &'life1 Context,
         event: Event<EventExtra>,
     ) -> core::pin::Pin<
-        Box<dyn core::future::Future<Output = ()> + core::marker::Send + 'async_trait>,
+        Box<dyn core::future::Future<Output = HandlerResult> + core::marker::Send + 'async_trait>,
     >
     where
         'life0: 'async_trait,
@@ -80,6 +427,7 @@ impl EventHandler for tokio::sync::mpsc::UnboundedSender<Event<EventExtra>> {
     {
         Box::pin(async move {
             self.send(event).ok();
+            Ok(())
         })
     }
 }
@@ -87,10 +435,10 @@ impl EventHandler for tokio::sync::mpsc::UnboundedSender<Event<EventExtra>> {
 impl EventHandler for tokio::sync::watch::Sender<Event<EventExtra>> {
     fn _handle<'life0, 'life1, 'async_trait>(
         &'life0 self,
-        _: &'life1 Kook,
+        _: &'life1 Context,
         event: Event<EventExtra>,
     ) -> core::pin::Pin<
-        Box<dyn core::future::Future<Output = ()> + core::marker::Send + 'async_trait>,
+        Box<dyn core::future::Future<Output = HandlerResult> + core::marker::Send + 'async_trait>,
     >
     where
         'life0: 'async_trait,
@@ -99,6 +447,7 @@ impl EventHandler for tokio::sync::watch::Sender<Event<EventExtra>> {
     {
         Box::pin(async move {
             self.send(event).ok();
+            Ok(())
         })
     }
 }
@@ -107,10 +456,10 @@ impl EventHandler for tokio::sync::watch::Sender<Event<EventExtra>> {
 impl EventHandler for () {
     fn _handle<'life0, 'life1, 'async_trait>(
         &'life0 self,
-        _khl: &'life1 Kook,
+        _ctx: &'life1 Context,
         event: Event<EventExtra>,
     ) -> core::pin::Pin<
-        Box<dyn core::future::Future<Output = ()> + core::marker::Send + 'async_trait>,
+        Box<dyn core::future::Future<Output = HandlerResult> + core::marker::Send + 'async_trait>,
     >
     where
         'life0: 'async_trait,
@@ -118,6 +467,116 @@ impl EventHandler for () {
         Self: 'async_trait,
     {
         tracing::info!(target: crate::KOOK, "{:?}", event);
-        Box::pin(async move {})
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+#[tokio::test]
+async fn typed_handler_test() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct ReactionHandler(AtomicBool);
+
+    #[async_trait]
+    impl EventHandler for ReactionHandler {
+        async fn on_reaction_added(&self, _ctx: &Context, event: Event<Reaction>) -> HandlerResult {
+            assert_eq!(event.extra.emoji.name, "smile");
+            self.0.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    let handler = ReactionHandler::default();
+    let event = crate::test::added_reaction();
+    let ctx = Context::new(Kook::new_from_config(Config::default(), ()).arc(), &event);
+    handler._handle(&ctx, event).await.unwrap();
+    assert!(handler.0.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn error_handler_test() {
+    use std::sync::{Arc, Mutex};
+
+    struct Panics;
+
+    #[async_trait]
+    impl EventHandler for Panics {
+        async fn handle(&self, _ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+            if event.author_id == "1" {
+                panic!("boom");
+            }
+            Ok(())
+        }
+    }
+
+    struct Fails;
+
+    #[async_trait]
+    impl EventHandler for Fails {
+        async fn handle(&self, _ctx: &Context, _event: Event<EventExtra>) -> HandlerResult {
+            Err("failed".into())
+        }
+    }
+
+    #[derive(Default, Clone)]
+    struct Record(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl ErrorHandler for Record {
+        async fn on_error(&self, _ctx: &Context, event: &Event<EventExtra>, error: HandlerError) {
+            assert_eq!(event.msg_id, "xxx");
+            self.0.lock().unwrap().push(error.to_string());
+        }
     }
+
+    let event = crate::test::system_event(
+        "guild",
+        serde_json::json!({ "type": "unknown_event", "body": {} }),
+    );
+    let record = Record::default();
+    let kook = Kook::new_from_config(Config::default(), Panics)
+        .with_error_handler(record.clone())
+        .arc();
+    kook.dispatch(event.clone(), None).await;
+    let kook = Kook::new_from_config(Config::default(), Fails)
+        .with_error_handler(record.clone())
+        .arc();
+    kook.dispatch(event.clone(), None).await;
+    assert_eq!(
+        *record.0.lock().unwrap(),
+        vec!["handler panicked: boom".to_owned(), "failed".to_owned()]
+    );
+
+    record.0.lock().unwrap().clear();
+    let kook = Kook::new_from_config(Config::default(), Panics)
+        .with_handler(Fails)
+        .with_error_handler(record.clone())
+        .arc();
+    kook.dispatch(event.clone(), None).await;
+    assert_eq!(
+        *record.0.lock().unwrap(),
+        vec!["handler panicked: boom".to_owned(), "failed".to_owned()]
+    );
+
+    struct PanicsAfter(Record);
+
+    #[async_trait]
+    impl ErrorHandler for PanicsAfter {
+        async fn on_error(&self, ctx: &Context, event: &Event<EventExtra>, error: HandlerError) {
+            self.0.on_error(ctx, event, error).await;
+            panic!("error handler");
+        }
+    }
+
+    record.0.lock().unwrap().clear();
+    let kook = Kook::new_from_config(Config::default(), Panics)
+        .with_handler(Fails)
+        .with_error_handler(PanicsAfter(record.clone()))
+        .arc();
+    kook.dispatch(event, None).await;
+    assert_eq!(
+        *record.0.lock().unwrap(),
+        vec!["handler panicked: boom".to_owned(), "failed".to_owned()]
+    );
 }
@@ -23,13 +23,25 @@ pub enum KMDItem {
     NewLine,
 }
 
-fn v2s(v: &Vec<KMDItem>) -> String {
+/// Escape `s` so KMarkdown shows it as is.
+pub fn kmd_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_ascii() && parse::ESCAPE_CHAR.contains(&(c as u8)) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn v2s(v: &[KMDItem]) -> String {
     v.iter().map(ToString::to_string).collect()
 }
 
-impl ToString for KMDItem {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for KMDItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
             Self::Text(s) => s.to_string(),
             Self::Blod(v) => format!("**{}**", v2s(v)),
             Self::Italic(v) => format!("*{}*", v2s(v)),
@@ -47,7 +59,8 @@ impl ToString for KMDItem {
             Self::InlineCode(s) => format!("`{s}`"),
             Self::Code { ty, content } => format!("```{ty}\n{content}```"),
             Self::NewLine => "\n".to_owned(),
-        }
+        };
+        f.write_str(&s)
     }
 }
 
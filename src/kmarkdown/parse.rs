@@ -38,7 +38,7 @@ const SPC_TABLE: &[u8; 13] = b"\n\r*~[])->(:`\\";
 // [
 //     b'\n', b'\r', b'*', b'~', b'[', b']', b')', b'-', b'>', b'(', b':', b'`', b'\\',
 // ];
-const ESCAPE_CHAR: &[u8; 13] = b"!()*-.:>[\\]`~";
+pub(crate) const ESCAPE_CHAR: &[u8; 13] = b"!()*-.:>[\\]`~";
 
 pub(crate) fn parse_str(s: &str) -> Vec<Item> {
     FirstPass {
@@ -102,7 +102,7 @@ impl<'a> FirstPass<'a> {
                 .push_and_return(ix, 3, b"```", ItemBody::Code)
                 .unwrap_or_else(|| self.push(ix, ItemBody::InlineCode)),
             b'\\' => {
-                if self.text.len() >= ix + 1 {
+                if self.text.len() > ix + 1 {
                     if ESCAPE_CHAR.contains(&self.text[ix + 1]) {
                         self.items.push(Item {
                             start: ix + 1,
@@ -136,15 +136,13 @@ impl<'a> FirstPass<'a> {
         expect: &[u8],
         body: ItemBody,
     ) -> Option<usize> {
-        if self.text.len() >= ix + offset {
-            if &self.text[ix..ix + offset] == expect {
-                self.items.push(Item {
-                    start: ix,
-                    end: ix + offset,
-                    body,
-                });
-                return Some(ix + offset);
-            }
+        if self.text.len() >= ix + offset && &self.text[ix..ix + offset] == expect {
+            self.items.push(Item {
+                start: ix,
+                end: ix + offset,
+                body,
+            });
+            return Some(ix + offset);
         }
         None
     }
@@ -514,3 +512,10 @@ fn err_test() {
     let temp0 = r#"(met)**youmet) /echo a"#;
     println!("{:?}", kmd_from_str(temp0));
 }
+
+#[test]
+fn trailing_backslash_test() {
+    let kmds = kmd_from_str(r"abc\");
+    let text = kmds.iter().map(|kmd| kmd.to_string()).collect::<String>();
+    assert_eq!(text, r"abc\");
+}
@@ -1,38 +1,60 @@
 mod api;
+pub mod cache;
 pub mod card;
+mod collector;
+pub mod commands;
 mod config;
+mod context;
+mod data;
+mod dispatch;
 mod error;
 mod event;
 mod handler;
+mod middleware;
 mod net;
 mod objects;
+mod presence;
+mod router;
 mod signal;
+mod stream;
 mod structs;
 #[cfg(test)]
 mod test;
+mod timestamp;
 
 mod kmarkdown;
-pub use kmarkdown::{kmd_from_str, KMDItem};
+pub use kmarkdown::{kmd_escape, kmd_from_str, KMDItem};
 
 pub const KOOK: &str = "KOOK";
 
 pub mod prelude {
+    pub use crate::cache::{Cache, CachedMessage, MessageCache};
     pub use crate::config::Config;
+    pub use crate::context::Context;
+    pub use crate::data::TypeMap;
+    pub use crate::dispatch::{DispatchMode, OrderKey};
     pub use crate::error::*;
     pub use crate::event::*;
     pub use crate::handler::*;
+    pub use crate::middleware::*;
     pub use crate::objects::*;
+    pub use crate::presence::{PresenceChange, PresenceStream, PresenceTracker};
+    pub use crate::router::*;
+    pub use crate::stream::{Backpressure, EventStream, StreamOptions};
     pub use crate::structs::*;
+    pub use crate::timestamp::{Timestamp, TimestampOutOfRange};
     pub use crate::Kook;
+    pub use crate::MessageType;
     pub use crate::KOOK;
 }
 
 use handler::EventHandler;
+use serde::{Deserialize, Serialize};
 use std::sync::{
     atomic::{AtomicBool, AtomicI32},
-    Arc,
+    Arc, OnceLock,
 };
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 
 pub struct Kook {
     pub author: String,
@@ -44,7 +66,17 @@ pub struct Kook {
     limit: net::limit::GlobalRateLimit,
 
     http_client: net::http::HttpsClient,
-    handler: Arc<dyn EventHandler>,
+    handler: router::Router,
+    middlewares: Vec<Arc<dyn middleware::Middleware>>,
+    data: data::TypeMap,
+    collectors: collector::Collectors,
+    error_handler: Arc<dyn handler::ErrorHandler>,
+    dispatcher: dispatch::Dispatcher,
+    feeder: OnceLock<mpsc::UnboundedSender<event::Event<event::EventExtra>>>,
+    subscribers: stream::Subscribers,
+    cache: Option<cache::Cache>,
+    message_cache: Option<cache::MessageCache>,
+    presence: Option<presence::PresenceTracker>,
 }
 
 impl Kook {
@@ -63,7 +95,17 @@ impl Kook {
             limit: net::limit::GlobalRateLimit::default(),
 
             http_client: Self::new_https_client(),
-            handler: Arc::new(hanlder),
+            handler: router::Router::new().handler(hanlder).handler(card::Clicks),
+            middlewares: vec![],
+            data: data::TypeMap::default(),
+            collectors: collector::Collectors::default(),
+            error_handler: Arc::new(handler::LogError),
+            dispatcher: dispatch::Dispatcher::new(config.dispatch),
+            feeder: OnceLock::new(),
+            subscribers: stream::Subscribers::default(),
+            cache: None,
+            message_cache: None,
+            presence: None,
         }
     }
 
@@ -72,7 +114,8 @@ impl Kook {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u8", into = "u8")]
 pub enum MessageType {
     Text,
     Image,
@@ -82,6 +125,7 @@ pub enum MessageType {
     KMarkdown,
     Card,
     System,
+    Unknown(u8),
 }
 
 impl From<MessageType> for u8 {
@@ -95,6 +139,23 @@ impl From<MessageType> for u8 {
             MessageType::KMarkdown => 9,
             MessageType::Card => 10,
             MessageType::System => 255,
+            MessageType::Unknown(u) => u,
+        }
+    }
+}
+
+impl From<u8> for MessageType {
+    fn from(u: u8) -> Self {
+        match u {
+            1 => MessageType::Text,
+            2 => MessageType::Image,
+            3 => MessageType::Video,
+            4 => MessageType::File,
+            8 => MessageType::Audio,
+            9 => MessageType::KMarkdown,
+            10 => MessageType::Card,
+            255 => MessageType::System,
+            u => MessageType::Unknown(u),
         }
     }
 }
@@ -0,0 +1,275 @@
+use async_trait::async_trait;
+use dashmap::{mapref::entry::Entry, DashMap};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, info, info_span, Instrument};
+
+use crate::prelude::*;
+
+/// A layer around `EventHandler::_handle`.
+///
+/// Call `next.run(ctx, event)` to pass the (possibly modified) event on,
+/// or return without calling it to drop the event.
+///
+/// Handlers and `Button::on_click` callbacks run inside the chain, while
+/// `Kook::wait_for` and `Kook::events` see every event before it.
+#[async_trait]
+pub trait Middleware: Sync + Send {
+    async fn call(&self, ctx: &Context, event: Event<EventExtra>, next: Next<'_>) -> HandlerResult;
+}
+
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn Middleware>],
+    handler: &'a dyn EventHandler,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        middlewares: &'a [Arc<dyn Middleware>],
+        handler: &'a dyn EventHandler,
+    ) -> Self {
+        Self {
+            middlewares,
+            handler,
+        }
+    }
+
+    pub async fn run(self, ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+        match self.middlewares.split_first() {
+            Some((first, rest)) => first.call(ctx, event, Next::new(rest, self.handler)).await,
+            None => self.handler._handle(ctx, event).await,
+        }
+    }
+}
+
+impl Kook {
+    pub fn with_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware + 'static,
+    {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn with_error_handler<E>(mut self, error_handler: E) -> Self
+    where
+        E: ErrorHandler + 'static,
+    {
+        self.error_handler = Arc::new(error_handler);
+        self
+    }
+
+    /// Pass `error` to the error handler, once for each error of `HandlerErrors`,
+    /// a panicking error handler is only logged.
+    pub(crate) async fn report_error(
+        &self,
+        ctx: &Context,
+        event: &Event<EventExtra>,
+        error: HandlerError,
+    ) {
+        let mut errors = vec![error];
+        while let Some(error) = errors.pop() {
+            match error.downcast::<HandlerErrors>() {
+                Ok(many) => errors.extend(many.0.into_iter().rev()),
+                Err(error) => {
+                    let report = async {
+                        self.error_handler.on_error(ctx, event, error).await;
+                        Ok(())
+                    };
+                    if let Err(e) = catch_panic(report).await {
+                        error!(target: KOOK, "error handler failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run the middlewares and handlers for `event`, `previous_message` is
+    /// what the message cache held before it.
+    pub(crate) async fn dispatch(
+        self: &Arc<Self>,
+        event: Event<EventExtra>,
+        previous_message: Option<CachedMessage>,
+    ) {
+        let span = info_span!(
+            target: KOOK,
+            "event",
+            msg_id = %event.msg_id,
+            ty = ?event.ty,
+            channel_type = ?event.channel_type,
+        );
+        let handle = async {
+            let mut ctx = Context::new(self.clone(), &event);
+            ctx.previous_message = previous_message;
+            let next = Next::new(&self.middlewares, &self.handler);
+            if let Err(e) = catch_panic(next.run(&ctx, event.clone())).await {
+                self.report_error(&ctx, &event, e).await;
+            }
+            Ok(())
+        };
+        async {
+            if let Err(e) = catch_panic(handle).await {
+                error!(target: KOOK, "failed to dispatch event: {}", e);
+            }
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+/// Log how long each event takes to handle.
+pub struct Timing;
+
+#[async_trait]
+impl Middleware for Timing {
+    async fn call(&self, ctx: &Context, event: Event<EventExtra>, next: Next<'_>) -> HandlerResult {
+        let msg_id = event.msg_id.clone();
+        let start = Instant::now();
+        let r = next.run(ctx, event).await;
+        info!(target: KOOK, "handled event {} in {:?}", msg_id, start.elapsed());
+        r
+    }
+}
+
+/// Drop events authored by the given users, and their button clicks.
+#[derive(Debug, Default)]
+pub struct Blocklist(pub HashSet<String>);
+
+#[async_trait]
+impl Middleware for Blocklist {
+    async fn call(&self, ctx: &Context, event: Event<EventExtra>, next: Next<'_>) -> HandlerResult {
+        let clicker = match &event.extra {
+            EventExtra::System(SystemExtra::MessageBtnClick(b)) => Some(&b.user_id),
+            _ => None,
+        };
+        if self.0.contains(&event.author_id) || clicker.is_some_and(|u| self.0.contains(u)) {
+            debug!(target: KOOK, "blocked event from {}", event.author_id);
+            return Ok(());
+        }
+        next.run(ctx, event).await
+    }
+}
+
+/// Drop message events from a user that arrive within `duration` of their last one.
+#[derive(Debug)]
+pub struct Cooldown {
+    duration: Duration,
+    last: DashMap<String, Instant>,
+    /// Size of `last` at which expired entries are swept next.
+    sweep_at: AtomicUsize,
+}
+
+impl Cooldown {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            last: DashMap::default(),
+            sweep_at: AtomicUsize::new(MIN_SWEEP),
+        }
+    }
+
+    /// Whether `user_id` is cooling down, starts a new cooldown if not.
+    fn check(&self, user_id: &str, now: Instant) -> bool {
+        let cooling = match self.last.entry(user_id.to_owned()) {
+            Entry::Occupied(mut last) if now.duration_since(*last.get()) >= self.duration => {
+                last.insert(now);
+                false
+            }
+            Entry::Occupied(_) => true,
+            Entry::Vacant(last) => {
+                last.insert(now);
+                false
+            }
+        };
+        if self.last.len() >= self.sweep_at.load(Ordering::Relaxed) {
+            self.last
+                .retain(|_, last| now.duration_since(*last) < self.duration);
+            self.sweep_at
+                .store((self.last.len() * 2).max(MIN_SWEEP), Ordering::Relaxed);
+        }
+        cooling
+    }
+}
+
+const MIN_SWEEP: usize = 64;
+
+#[async_trait]
+impl Middleware for Cooldown {
+    async fn call(&self, ctx: &Context, event: Event<EventExtra>, next: Next<'_>) -> HandlerResult {
+        if event.author().is_some() && self.check(&event.author_id, Instant::now()) {
+            debug!(target: KOOK, "user {} is cooling down", event.author_id);
+            return Ok(());
+        }
+        next.run(ctx, event).await
+    }
+}
+
+#[tokio::test]
+async fn middleware_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counter(AtomicUsize);
+
+    #[async_trait]
+    impl EventHandler for Counter {
+        async fn handle(&self, _ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+            assert_eq!(event.content, "HELLO");
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    struct Upper;
+
+    #[async_trait]
+    impl Middleware for Upper {
+        async fn call(
+            &self,
+            ctx: &Context,
+            mut event: Event<EventExtra>,
+            next: Next<'_>,
+        ) -> HandlerResult {
+            event.content = event.content.to_uppercase();
+            next.run(ctx, event).await
+        }
+    }
+
+    let event = crate::test::group_message("hello");
+    let counter = Counter::default();
+    let middlewares: Vec<Arc<dyn Middleware>> = vec![
+        Arc::new(Cooldown::new(Duration::from_secs(60))),
+        Arc::new(Upper),
+    ];
+    let ctx = Context::new(Kook::new_from_config(Config::default(), ()).arc(), &event);
+    Next::new(&middlewares, &counter)
+        .run(&ctx, event.clone())
+        .await
+        .unwrap();
+    Next::new(&middlewares, &counter)
+        .run(&ctx, event)
+        .await
+        .unwrap();
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn cooldown_test() {
+    let now = Instant::now();
+    let cooldown = Cooldown::new(Duration::from_secs(60));
+    assert!(!cooldown.check("a", now));
+    assert!(cooldown.check("a", now + Duration::from_secs(1)));
+    assert!(!cooldown.check("a", now + Duration::from_secs(61)));
+
+    let cooldown = Cooldown::new(Duration::from_secs(1));
+    for i in 0..200 {
+        cooldown.check(&i.to_string(), now + Duration::from_secs(i));
+    }
+    assert!(cooldown.last.len() < MIN_SWEEP);
+}
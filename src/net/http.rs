@@ -47,7 +47,7 @@ impl crate::Kook {
             use hyper::body::to_bytes;
             let bytes = to_bytes(res.into_body())
                 .await
-                .map_err(|e| KookError::HyperError(e))?;
+                .map_err(KookError::HyperError)?;
             let s = String::from_utf8(bytes.to_vec()).unwrap();
             trace!(target: crate::KOOK, "post resp: {:?}", s);
             let data: HttpResp<EmptyAble<T>> = serde_json::from_str(&s)?;
@@ -55,7 +55,7 @@ impl crate::Kook {
         }
         #[cfg(not(test))]
         {
-            let body = aggregate(res).await.map_err(|e| KookError::HyperError(e))?;
+            let body = aggregate(res).await.map_err(KookError::HyperError)?;
             let data: HttpResp<EmptyAble<T>> = serde_json::from_reader(body.reader())?;
             data.as_result()
         }
@@ -82,7 +82,7 @@ impl crate::Kook {
             use hyper::body::to_bytes;
             let bytes = to_bytes(res.into_body())
                 .await
-                .map_err(|e| KookError::HyperError(e))?;
+                .map_err(KookError::HyperError)?;
             let s = String::from_utf8(bytes.to_vec()).unwrap();
             tracing::trace!(target: crate::KOOK, "get resp: {:?}", s);
             let data: HttpResp<EmptyAble<T>> = serde_json::from_str(&s)?;
@@ -182,6 +182,7 @@ pub enum EmptyAble<T> {
 }
 
 impl<T> HttpResp<EmptyAble<T>> {
+    #[allow(clippy::wrong_self_convention)]
     pub fn as_result(self) -> KookResult<T> {
         if self.code == 0 {
             match self.data {
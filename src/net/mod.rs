@@ -1,4 +1,4 @@
 pub mod http;
-pub mod ws;
+pub(crate) mod limit;
 pub mod wh;
-pub(crate) mod limit;
\ No newline at end of file
+pub mod ws;
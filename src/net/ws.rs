@@ -9,7 +9,7 @@ use tokio::net::TcpStream;
 use tokio_tungstenite::{
     connect_async, tungstenite::Message as WsMsg, MaybeTlsStream, WebSocketStream,
 };
-use tracing::{trace, warn, debug};
+use tracing::{debug, trace, warn};
 
 impl crate::Kook {
     pub async fn start_ws(self: &Arc<Self>) -> KookResult<()> {
@@ -67,16 +67,11 @@ impl crate::Kook {
                         match sig {
                             Signal::Event(event, sn) => {
                                 self.sn.store(sn, Ordering::SeqCst);
-                                let khl = self.clone();
-                                if self.bot_block {
-                                    if event.author().map_or(false, |user| user.bot) {
-                                        debug!(target: KOOK, "blocked a bot event");
-                                        return false;
-                                    }
+                                if self.bot_block && event.author().is_some_and(|user| user.bot) {
+                                    debug!(target: KOOK, "blocked a bot event");
+                                    return false;
                                 }
-                                tokio::spawn(async move {
-                                    khl.handler._handle(&khl, event).await;
-                                });
+                                self.submit(event);
                                 false
                             }
                             Signal::Hello(hello) => {
@@ -1,3 +1,4 @@
+use crate::timestamp::Timestamp;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,13 +57,44 @@ pub struct Channel {
     pub level: i32,
     pub slow_mode: i32,
     #[serde(rename = "type")]
-    pub ty: i32,
+    pub ty: ChannelKind,
     pub permission_overwrites: Vec<PermissionOverwrite>,
     pub permission_users: Vec<PermissionUser>,
     pub permission_sync: i32,
     pub has_password: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u8", into = "u8")]
+pub enum ChannelKind {
+    Category,
+    Text,
+    Voice,
+    Unknown(u8),
+}
+
+impl From<ChannelKind> for u8 {
+    fn from(kind: ChannelKind) -> Self {
+        match kind {
+            ChannelKind::Category => 0,
+            ChannelKind::Text => 1,
+            ChannelKind::Voice => 2,
+            ChannelKind::Unknown(u) => u,
+        }
+    }
+}
+
+impl From<u8> for ChannelKind {
+    fn from(u: u8) -> Self {
+        match u {
+            0 => ChannelKind::Category,
+            1 => ChannelKind::Text,
+            2 => ChannelKind::Voice,
+            u => ChannelKind::Unknown(u),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionOverwrite {
     pub role_id: i32,
@@ -81,9 +113,9 @@ pub struct PermissionUser {
 pub struct Quote {
     pub id: String,
     #[serde(rename = "type")]
-    pub ty: i32,
+    pub ty: crate::MessageType,
     pub content: String,
-    pub create_at: i32,
+    pub create_at: Timestamp,
     pub author: User,
 }
 
@@ -93,7 +125,35 @@ pub struct Attachments {
     pub ty: String,
     pub url: String,
     pub name: String,
-    pub size: i32,
+    #[serde(default)]
+    pub size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageKMarkdown {
+    pub raw_content: String,
+    #[serde(default)]
+    pub mention_part: Vec<MentionPart>,
+    #[serde(default)]
+    pub mention_role_part: Vec<MentionRolePart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MentionPart {
+    pub id: String,
+    pub username: String,
+    #[serde(default)]
+    pub full_name: String,
+    #[serde(default)]
+    pub avatar: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MentionRolePart {
+    pub role_id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub color: i32,
 }
 
 use serde::de::{Deserializer, Visitor};
@@ -0,0 +1,249 @@
+use dashmap::DashMap;
+use futures_util::Stream;
+use std::{
+    collections::HashSet,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context as TaskContext, Poll},
+};
+use tokio::sync::mpsc;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresenceChange {
+    pub guild_id: String,
+    pub user_id: String,
+    pub online: bool,
+    pub event_time: Timestamp,
+}
+
+#[derive(Debug, Default)]
+struct GuildPresence {
+    online: HashSet<String>,
+    /// Reported by the API, may include members not in `online` yet.
+    count: usize,
+}
+
+/// Per guild online members, kept up to date from `GuildMemberOnline` and
+/// `GuildMemberOffline` events. Enable with `Kook::with_presence`.
+#[derive(Debug, Default)]
+pub struct PresenceTracker {
+    guilds: DashMap<String, GuildPresence>,
+    watchers: Mutex<Vec<mpsc::UnboundedSender<PresenceChange>>>,
+}
+
+impl PresenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn online_members(&self, guild_id: &str) -> Vec<String> {
+        self.guilds
+            .get(guild_id)
+            .map(|g| g.online.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn is_online(&self, guild_id: &str, user_id: &str) -> bool {
+        self.guilds
+            .get(guild_id)
+            .is_some_and(|g| g.online.contains(user_id))
+    }
+
+    /// Online count as reported by the API, adjusted by events since.
+    pub fn online_count(&self, guild_id: &str) -> usize {
+        self.guilds
+            .get(guild_id)
+            .map(|g| g.count.max(g.online.len()))
+            .unwrap_or_default()
+    }
+
+    /// Replace the state of a guild with a user list, `online_count` is
+    /// taken from the first page.
+    pub fn seed(&self, guild_id: &str, list: &GuildUserList) {
+        let mut guild = self.guilds.entry(guild_id.to_owned()).or_default();
+        if list.meta.page <= 1 {
+            guild.online.clear();
+            guild.count = list.online_count.max(0) as usize;
+        }
+        guild
+            .online
+            .extend(list.items.iter().filter(|u| u.online).map(|u| u.id.clone()));
+    }
+
+    /// A stream of every change applied from events.
+    pub fn changes(&self) -> PresenceStream {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.watchers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(tx);
+        PresenceStream(rx)
+    }
+
+    fn set(&self, guild_id: &str, user_id: &str, online: bool) -> bool {
+        let mut guild = self.guilds.entry(guild_id.to_owned()).or_default();
+        if online {
+            let changed = guild.online.insert(user_id.to_owned());
+            if changed {
+                guild.count += 1;
+            }
+            changed
+        } else {
+            let changed = guild.online.remove(user_id);
+            // the user may be online without being in the seeded pages
+            if changed || guild.count > guild.online.len() {
+                guild.count = guild.count.saturating_sub(1);
+            }
+            changed
+        }
+    }
+
+    pub(crate) fn update(&self, event: &Event<EventExtra>) {
+        let (guilds, user_id, online, event_time) = match &event.extra {
+            EventExtra::System(SystemExtra::GuildMemberOnline(p)) => {
+                (&p.guilds[..], &p.user_id, true, p.event_time)
+            }
+            EventExtra::System(SystemExtra::GuildMemberOffline(p)) => {
+                (&p.guilds[..], &p.user_id, false, p.event_time)
+            }
+            EventExtra::System(SystemExtra::ExitedGuild(m)) => (
+                std::slice::from_ref(&event.target_id),
+                &m.user_id,
+                false,
+                m.exited_at,
+            ),
+            _ => return,
+        };
+        let mut changes = vec![];
+        for guild_id in guilds {
+            if self.set(guild_id, user_id, online) {
+                changes.push(PresenceChange {
+                    guild_id: guild_id.clone(),
+                    user_id: user_id.clone(),
+                    online,
+                    event_time,
+                });
+            }
+        }
+        if changes.is_empty() {
+            return;
+        }
+        let mut watchers = self.watchers.lock().unwrap_or_else(|e| e.into_inner());
+        watchers.retain(|tx| changes.iter().all(|c| tx.send(c.clone()).is_ok()));
+    }
+}
+
+pub struct PresenceStream(mpsc::UnboundedReceiver<PresenceChange>);
+
+impl Stream for PresenceStream {
+    type Item = PresenceChange;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+impl Kook {
+    pub fn with_presence(mut self, presence: PresenceTracker) -> Self {
+        self.presence = Some(presence);
+        self
+    }
+
+    pub fn presence(&self) -> Option<&PresenceTracker> {
+        self.presence.as_ref()
+    }
+
+    /// Fetch every member of a guild to seed the presence tracker.
+    pub async fn load_presence(&self, guild_id: &str) -> KookResult<()> {
+        let mut page = 1;
+        loop {
+            let list = self
+                .get_guild_user_list(
+                    guild_id,
+                    None,
+                    None,
+                    None,
+                    false,
+                    false,
+                    false,
+                    Some(page),
+                    Some(50),
+                    None,
+                )
+                .await?;
+            if let Some(presence) = self.presence() {
+                presence.seed(guild_id, &list);
+            }
+            if list.meta.page >= list.meta.page_total {
+                return Ok(());
+            }
+            page += 1;
+        }
+    }
+}
+
+#[tokio::test]
+async fn presence_test() {
+    use futures_util::StreamExt;
+
+    let tracker = PresenceTracker::new();
+    let list: GuildUserList = serde_json::from_value(serde_json::json!({
+        "items": [crate::test::user("a")],
+        "meta": { "page": 1, "page_total": 1, "page_size": 50, "total": 1 },
+        "user_count": 10,
+        "online_count": 3,
+        "offline_count": 7
+    }))
+    .unwrap();
+    tracker.seed("guild", &list);
+    assert!(tracker.is_online("guild", "a"));
+    assert_eq!(tracker.online_count("guild"), 3);
+
+    let mut changes = tracker.changes();
+    let mut event = crate::test::system_event(
+        "bot",
+        serde_json::json!({
+            "type": "guild_member_online",
+            "body": { "user_id": "b", "event_time": 1612778254192u64, "guilds": ["guild"] }
+        }),
+    );
+    tracker.update(&event);
+    assert_eq!(tracker.online_count("guild"), 4);
+    let change = changes.next().await.unwrap();
+    assert_eq!((change.user_id.as_str(), change.online), ("b", true));
+
+    if let EventExtra::System(SystemExtra::GuildMemberOnline(p)) = event.extra {
+        event.extra = EventExtra::System(SystemExtra::GuildMemberOffline(p));
+    }
+    tracker.update(&event);
+    assert!(!tracker.is_online("guild", "b"));
+    assert_eq!(tracker.online_members("guild"), vec!["a"]);
+    assert!(!changes.next().await.unwrap().online);
+    assert_eq!(tracker.online_count("guild"), 3);
+    // offline users not in the seeded pages count until only known ones are left
+    tracker.update(&event);
+    tracker.update(&event);
+    tracker.update(&event);
+    assert_eq!(tracker.online_count("guild"), 1);
+
+    event.target_id = "guild".to_owned();
+    event.extra = EventExtra::System(SystemExtra::ExitedGuild(ExitedGuild {
+        user_id: "a".to_owned(),
+        exited_at: Timestamp::from_millis(1612778254192),
+    }));
+    tracker.update(&event);
+    assert!(!tracker.is_online("guild", "a"));
+    assert_eq!(tracker.online_count("guild"), 0);
+    let change = changes.next().await.unwrap();
+    assert_eq!((change.user_id.as_str(), change.online), ("a", false));
+
+    // later pages may list more online members than the first page counted
+    let mut list = list;
+    list.online_count = 0;
+    tracker.seed("stale", &list);
+    event.target_id = "stale".to_owned();
+    tracker.update(&event);
+    assert_eq!(tracker.online_count("stale"), 0);
+}
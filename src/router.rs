@@ -0,0 +1,197 @@
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use regex::Regex;
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+type Predicate = dyn Fn(&Event<EventExtra>) -> bool + Send + Sync;
+
+/// A predicate on events, combine with `and`, `or` and `not`.
+#[derive(Clone)]
+pub struct Filter(Arc<Predicate>);
+
+impl Filter {
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&Event<EventExtra>) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    pub fn matches(&self, event: &Event<EventExtra>) -> bool {
+        (self.0)(event)
+    }
+
+    pub fn all() -> Self {
+        Self::new(|_| true)
+    }
+
+    pub fn guild(guild_id: &str) -> Self {
+        let guild_id = guild_id.to_owned();
+        Self::new(move |e| e.guild_id() == Some(guild_id.as_str()))
+    }
+
+    pub fn channel(channel_id: &str) -> Self {
+        let channel_id = channel_id.to_owned();
+        Self::new(move |e| e.channel_id() == Some(channel_id.as_str()))
+    }
+
+    pub fn author(user_id: &str) -> Self {
+        let user_id = user_id.to_owned();
+        Self::new(move |e| e.author().is_some() && e.author_id == user_id)
+    }
+
+    pub fn message_type(ty: MessageType) -> Self {
+        Self::new(move |e| e.author().is_some() && e.ty == ty)
+    }
+
+    pub fn group_message() -> Self {
+        Self::new(|e| matches!(e.extra, EventExtra::GroupMessage(_)))
+    }
+
+    pub fn person_message() -> Self {
+        Self::new(|e| matches!(e.extra, EventExtra::PersonMessage(_)))
+    }
+
+    /// Message events whose content matches `regex`.
+    pub fn content(regex: Regex) -> Self {
+        Self::new(move |e| e.author().is_some() && regex.is_match(&e.content))
+    }
+
+    /// Message events mentioning `user_id`, pass the bot id to match mentions of the bot.
+    pub fn mention(user_id: &str) -> Self {
+        let user_id = user_id.to_owned();
+        Self::new(move |e| match &e.extra {
+            EventExtra::GroupMessage(g) => g.mention.contains(&user_id),
+            EventExtra::PersonMessage(p) => p.mention.contains(&user_id),
+            EventExtra::System(_) => false,
+        })
+    }
+
+    /// System events of the given `type`, e.g. `"added_reaction"`.
+    pub fn system(ty: &str) -> Self {
+        let ty = ty.to_owned();
+        Self::new(move |e| match &e.extra {
+            EventExtra::System(s) => s.ty() == ty,
+            _ => false,
+        })
+    }
+
+    pub fn and(self, other: Filter) -> Self {
+        Self::new(move |e| self.matches(e) && other.matches(e))
+    }
+
+    pub fn or(self, other: Filter) -> Self {
+        Self::new(move |e| self.matches(e) || other.matches(e))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Self::new(move |e| !self.matches(e))
+    }
+}
+
+/// Dispatch events to every handler whose filter matches.
+#[derive(Clone, Default)]
+pub struct Router {
+    routes: Vec<(Filter, Arc<dyn EventHandler>)>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn route<H>(mut self, filter: Filter, handler: H) -> Self
+    where
+        H: EventHandler + 'static,
+    {
+        self.routes.push((filter, Arc::new(handler)));
+        self
+    }
+
+    pub fn handler<H>(self, handler: H) -> Self
+    where
+        H: EventHandler + 'static,
+    {
+        self.route(Filter::all(), handler)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+#[async_trait]
+impl EventHandler for Router {
+    /// Every matching handler runs even if another fails, several errors
+    /// are returned as `HandlerErrors`.
+    async fn _handle(&self, ctx: &Context, event: Event<EventExtra>) -> HandlerResult {
+        let results = join_all(
+            self.routes
+                .iter()
+                .filter(|(filter, _)| filter.matches(&event))
+                .map(|(_, handler)| catch_panic(handler._handle(ctx, event.clone()))),
+        )
+        .await;
+        let mut errors: Vec<_> = results.into_iter().filter_map(Result::err).collect();
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(Box::new(HandlerErrors(errors))),
+        }
+    }
+}
+
+impl Kook {
+    pub fn with_handler<H>(mut self, handler: H) -> Self
+    where
+        H: EventHandler + 'static,
+    {
+        self.handler = self.handler.handler(handler);
+        self
+    }
+
+    pub fn with_route<H>(mut self, filter: Filter, handler: H) -> Self
+    where
+        H: EventHandler + 'static,
+    {
+        self.handler = self.handler.route(filter, handler);
+        self
+    }
+}
+
+#[tokio::test]
+async fn router_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default, Clone)]
+    struct Counter(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl EventHandler for Counter {
+        async fn handle(&self, _ctx: &Context, _event: Event<EventExtra>) -> HandlerResult {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    let event = crate::test::group_message("(met)bot(met) ping");
+    let (matched, skipped) = (Counter::default(), Counter::default());
+    let router = Router::new()
+        .route(
+            Filter::guild("guild")
+                .and(Filter::channel("channel"))
+                .and(Filter::mention("bot"))
+                .and(Filter::content(Regex::new("ping$").unwrap())),
+            matched.clone(),
+        )
+        .route(Filter::author("user").not(), skipped.clone())
+        .route(Filter::system("added_reaction"), skipped.clone())
+        .handler(matched.clone());
+    let ctx = Context::new(Kook::new_from_config(Config::default(), ()).arc(), &event);
+    router._handle(&ctx, event).await.unwrap();
+    assert_eq!(matched.0.load(Ordering::SeqCst), 2);
+    assert_eq!(skipped.0.load(Ordering::SeqCst), 0);
+}
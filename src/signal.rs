@@ -2,6 +2,7 @@ use crate::prelude::*;
 use serde::{de::Visitor, Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Signal {
     Event(Event<EventExtra>, i32),
     Hello(HelloContent),
@@ -58,19 +59,12 @@ impl<'de> Visitor<'de> for SignalVistor {
     where
         A: serde::de::MapAccess<'de>,
     {
+        use crate::event::de_with_path;
         use serde::de::Error as DeError;
-
-        #[derive(Deserialize)]
-        #[serde(untagged)]
-        enum DItem {
-            Event(Event<EventExtra>),
-            Hello(HelloContent),
-            Reconnect(ReconnectContent),
-            ResumeAck(ResumeAckContent),
-        }
+        use serde_json::Value as JsonValue;
 
         let mut s: Option<u8> = None;
-        let mut d: Option<DItem> = None;
+        let mut d: Option<JsonValue> = None;
         let mut sn: Option<i32> = None;
         while let Some(key) = map.next_key()? {
             match key {
@@ -82,41 +76,30 @@ impl<'de> Visitor<'de> for SignalVistor {
                 }
             }
         }
+        let d = d.ok_or(DeError::missing_field("d"));
         match s.ok_or(DeError::missing_field("s"))? {
             0 => {
-                if let DItem::Event(event) = d.ok_or(DeError::missing_field("d"))? {
-                    Ok(Signal::Event(
-                        event,
-                        sn.ok_or(DeError::missing_field("sn"))?,
-                    ))
-                } else {
-                    Err(DeError::custom("d should be event"))
-                }
-            }
-            1 => {
-                if let DItem::Hello(hello) = d.ok_or(DeError::missing_field("d"))? {
-                    Ok(Signal::Hello(hello))
-                } else {
-                    Err(DeError::custom("d should be hello"))
-                }
+                let d = d?;
+                let mut event: Event<EventExtra> =
+                    de_with_path(&d).map_err(|e| DeError::custom(format!("d.{}", e)))?;
+                event.raw = Some(std::sync::Arc::new(d));
+                Ok(Signal::Event(
+                    event,
+                    sn.ok_or(DeError::missing_field("sn"))?,
+                ))
             }
+            1 => Ok(Signal::Hello(
+                de_with_path(&d?).map_err(|e| DeError::custom(format!("d.{}", e)))?,
+            )),
             2 => Ok(Signal::Ping(sn.ok_or(DeError::missing_field("sn"))?)),
             3 => Ok(Signal::Pong),
             4 => Ok(Signal::Resume(sn.ok_or(DeError::missing_field("sn"))?)),
-            5 => {
-                if let DItem::Reconnect(reconnect) = d.ok_or(DeError::missing_field("d"))? {
-                    Ok(Signal::Reconnect(reconnect))
-                } else {
-                    Err(DeError::custom("d should be reconnect"))
-                }
-            }
-            6 => {
-                if let DItem::ResumeAck(resume_ack) = d.ok_or(DeError::missing_field("d"))? {
-                    Ok(Signal::ResumeAck(resume_ack))
-                } else {
-                    Err(DeError::custom("d should be resume_ack"))
-                }
-            }
+            5 => Ok(Signal::Reconnect(
+                de_with_path(&d?).map_err(|e| DeError::custom(format!("d.{}", e)))?,
+            )),
+            6 => Ok(Signal::ResumeAck(
+                de_with_path(&d?).map_err(|e| DeError::custom(format!("d.{}", e)))?,
+            )),
             u => Err(DeError::unknown_variant(
                 &u.to_string(),
                 &["0", "1", "2", "3", "4", "5", "6"],
@@ -141,7 +124,13 @@ impl Serialize for Signal {
     {
         use serde::ser::SerializeMap;
         match self {
-            Signal::Event(..) => unimplemented!(),
+            Signal::Event(event, sn) => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("s", &0)?;
+                map.serialize_entry("d", &event)?;
+                map.serialize_entry("sn", &sn)?;
+                map.end()
+            }
             Signal::Hello(hello) => {
                 let mut map = serializer.serialize_map(Some(2))?;
                 map.serialize_entry("s", &1)?;
@@ -252,4 +241,72 @@ fn de_event_test() {
       }"#;
     let signal: Signal = serde_json::from_str(s).unwrap();
     println!("{:?}", signal);
+    match signal {
+        Signal::Event(event, _) => assert_eq!(event.raw.unwrap()["verify_token"], "xxx"),
+        _ => panic!("should be event"),
+    }
+}
+
+#[test]
+fn de_event_error_path_test() {
+    let s = r#"{
+        "s": 0,
+        "d": {
+          "channel_type": "GROUP",
+          "type": 1,
+          "target_id": "xxx",
+          "author_id": "1",
+          "content": "hello",
+          "extra": {
+            "type": 1,
+            "guild_id": "xxx",
+            "channel_name": "general",
+            "mention": [],
+            "mention_all": false,
+            "mention_roles": [],
+            "mention_here": false,
+            "author": {}
+          },
+          "msg_id": "xxx",
+          "msg_timestamp": 1612778254192,
+          "nonce": ""
+        },
+        "sn": 33
+      }"#;
+    let err = serde_json::from_str::<Signal>(s).unwrap_err().to_string();
+    assert!(err.contains("d.extra"), "{}", err);
+    assert!(err.contains("author"), "{}", err);
+}
+
+#[test]
+fn ser_event_test() {
+    let s = r#"{
+        "s": 0,
+        "d": {
+          "channel_type": "PERSON",
+          "type": 255,
+          "target_id": "2862900000",
+          "author_id": "1",
+          "content": "[系统消息]",
+          "extra": {
+            "type": "deleted_private_message",
+            "body": {
+              "author_id": "2862900000",
+              "target_id": "2862900000",
+              "msg_id": "93262503-xxxx-0d814f7b416a",
+              "chat_code": "xxxxxxxxxxxxxxxxx",
+              "deleted_at": 1612778254183
+            }
+          },
+          "msg_id": "8cb11d28-xxxxx-5700aa4c1b58",
+          "msg_timestamp": 1612778254192,
+          "nonce": ""
+        },
+        "sn": 32
+      }"#;
+    let signal: Signal = serde_json::from_str(s).unwrap();
+    let value = serde_json::to_value(&signal).unwrap();
+    assert_eq!(value, serde_json::from_str::<serde_json::Value>(s).unwrap());
+    let signal: Signal = serde_json::from_value(value).unwrap();
+    assert!(matches!(signal, Signal::Event(_, 32)));
 }
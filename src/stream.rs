@@ -0,0 +1,200 @@
+use dashmap::DashMap;
+use futures_util::Stream;
+use std::{
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context as TaskContext, Poll},
+};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::prelude::*;
+
+/// What to do when a subscriber's buffer is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Drop the event for this subscriber.
+    #[default]
+    Drop,
+    /// Wait until the subscriber catches up, delaying the handlers of this
+    /// and every later event.
+    Wait,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamOptions {
+    pub buffer: usize,
+    pub backpressure: Backpressure,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        Self {
+            buffer: 128,
+            backpressure: Backpressure::default(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Subscriber {
+    filter: Filter,
+    backpressure: Backpressure,
+    tx: mpsc::Sender<Event<EventExtra>>,
+}
+
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    next_id: AtomicU64,
+    subscribers: DashMap<u64, Subscriber>,
+}
+
+impl Subscribers {
+    pub(crate) async fn feed(&self, event: &Event<EventExtra>) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        let matched: Vec<(u64, Subscriber)> = self
+            .subscribers
+            .iter()
+            .filter(|entry| entry.value().filter.matches(event))
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+        for (id, subscriber) in matched {
+            let closed = match subscriber.backpressure {
+                Backpressure::Wait => subscriber.tx.send(event.clone()).await.is_err(),
+                Backpressure::Drop => match subscriber.tx.try_send(event.clone()) {
+                    Err(mpsc::error::TrySendError::Full(_)) => {
+                        debug!(target: KOOK, "event stream {} is full, dropped event", id);
+                        false
+                    }
+                    r => r.is_err(),
+                },
+            };
+            if closed {
+                self.subscribers.remove(&id);
+            }
+        }
+    }
+}
+
+/// A stream of events, ends when the `Kook` is dropped.
+pub struct EventStream<T> {
+    rx: mpsc::Receiver<Event<EventExtra>>,
+    map: fn(Event<EventExtra>) -> Option<Event<T>>,
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = Event<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.rx.poll_recv(cx) {
+                Poll::Ready(Some(event)) => {
+                    if let Some(event) = (self.map)(event) {
+                        return Poll::Ready(Some(event));
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Kook {
+    /// Subscribe to events matching `filter`, each call is an independent subscriber.
+    pub fn events_with(&self, filter: Filter, options: StreamOptions) -> EventStream<EventExtra> {
+        self.subscribe(filter, options, Some)
+    }
+
+    pub fn events(&self) -> EventStream<EventExtra> {
+        self.events_with(Filter::all(), StreamOptions::default())
+    }
+
+    /// Group and person messages.
+    pub fn messages(&self) -> EventStream<EventExtra> {
+        let filter = Filter::group_message().or(Filter::person_message());
+        self.events_with(filter, StreamOptions::default())
+    }
+
+    pub fn group_messages(&self) -> EventStream<GroupMessageExtra> {
+        self.subscribe(
+            Filter::group_message(),
+            StreamOptions::default(),
+            Event::down_case,
+        )
+    }
+
+    pub fn person_messages(&self) -> EventStream<PersonMessageExtra> {
+        self.subscribe(
+            Filter::person_message(),
+            StreamOptions::default(),
+            Event::down_case,
+        )
+    }
+
+    pub fn system_events(&self) -> EventStream<SystemExtra> {
+        let filter = Filter::new(|e| matches!(e.extra, EventExtra::System(_)));
+        self.subscribe(filter, StreamOptions::default(), Event::down_case)
+    }
+
+    fn subscribe<T>(
+        &self,
+        filter: Filter,
+        options: StreamOptions,
+        map: fn(Event<EventExtra>) -> Option<Event<T>>,
+    ) -> EventStream<T> {
+        let (tx, rx) = mpsc::channel(options.buffer.max(1));
+        let id = self.subscribers.next_id.fetch_add(1, Ordering::Relaxed);
+        self.subscribers.subscribers.insert(
+            id,
+            Subscriber {
+                filter,
+                backpressure: options.backpressure,
+                tx,
+            },
+        );
+        EventStream { rx, map }
+    }
+}
+
+#[tokio::test]
+async fn event_stream_test() {
+    use futures_util::StreamExt;
+
+    let kook = Kook::new_from_config(Config::default(), ()).arc();
+    let mut all = kook.events();
+    let mut system = kook.system_events();
+    let mut messages = kook.group_messages();
+    // never polled before both events are dispatched, must not block them
+    let mut lossy = kook.events_with(
+        Filter::all(),
+        StreamOptions {
+            buffer: 1,
+            ..StreamOptions::default()
+        },
+    );
+    let event = crate::test::added_reaction();
+    kook.submit(event.clone());
+    kook.submit(event);
+    assert_eq!(all.next().await.unwrap().msg_id, "xxx");
+    assert_eq!(all.next().await.unwrap().msg_id, "xxx");
+    assert!(matches!(
+        system.next().await.unwrap().extra,
+        SystemExtra::AddedReaction(_)
+    ));
+
+    // streams see events in arrival order, whatever order handlers run in
+    for i in 0..10 {
+        kook.submit(crate::test::group_message(&i.to_string()));
+    }
+    for i in 0..10 {
+        assert_eq!(messages.next().await.unwrap().content, i.to_string());
+    }
+    assert!(lossy.next().await.is_some());
+    assert!(lossy.rx.try_recv().is_err());
+    drop(kook);
+    assert!(lossy.next().await.is_none());
+    assert!(messages.next().await.is_none());
+}
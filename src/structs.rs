@@ -1,4 +1,5 @@
 use crate::objects::*;
+use crate::timestamp::Timestamp;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,7 +34,7 @@ pub struct ChannelShort {
     pub master_id: String,
     pub parent_id: String,
     #[serde(rename = "type")]
-    pub ty: u8,
+    pub ty: ChannelKind,
     pub level: i64,
     pub limit_amount: i64,
     pub is_category: bool,
@@ -48,7 +49,7 @@ pub struct ChannelView {
     pub name: String,
     pub topic: String,
     #[serde(rename = "type")]
-    pub ty: u8,
+    pub ty: ChannelKind,
     pub level: i64,
     pub slow_mode: i64,
     pub limit_amount: i64,
@@ -62,13 +63,13 @@ pub struct Emoji {
     pub name: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RespList<T> {
     pub items: Vec<T>,
     pub meta: PageMeta,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuildUserList {
     pub items: Vec<User>,
     pub meta: PageMeta,
@@ -76,32 +77,32 @@ pub struct GuildUserList {
     pub online_count: i32,
     pub offline_count: i32,
 }
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageResp {
     pub msg_id: String,
-    pub msg_timestamp: i64,
+    pub msg_timestamp: Timestamp,
     pub nonce: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuildMuteList {
     pub mic: GuildMuteItem,
     pub headset: GuildMuteItem,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuildMuteItem {
     #[serde(rename = "type")]
     pub ty: u8,
     pub user_ids: Vec<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Gateway {
     pub url: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DirectMessage {
     pub id: String,
     #[serde(rename = "type")]
@@ -109,8 +110,8 @@ pub struct DirectMessage {
     pub content: String,
     // pub embeds: Vec<>, todo
     pub attachments: Vec<Attachments>,
-    pub create_at: u64,
-    pub updated_at: u64,
+    pub create_at: Timestamp,
+    pub updated_at: Timestamp,
     // pub reactions: Vec<>, todo
     pub author_id: String,
     pub image_name: String,
@@ -119,20 +120,20 @@ pub struct DirectMessage {
     // pub mention_info: Option<String>, todo
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MuteList {
     pub mic: MuteItem,
     pub headset: MuteItem,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MuteItem {
     #[serde(rename = "type")]
     pub ty: u8,
     pub user_ids: Vec<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelRole {
     pub permission_overwrites: Vec<PermissionOverwrite>,
     pub permission_users: Vec<PermissionUser>,
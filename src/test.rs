@@ -19,21 +19,132 @@ fn init() -> Arc<Kook> {
     Kook::new_from_config(config, EchoHandler).arc()
 }
 
+/// A system event in the guild `target_id` with the `type` and `body` of `extra`.
+pub(crate) fn system_event(target_id: &str, extra: serde_json::Value) -> Event<EventExtra> {
+    serde_json::from_value(serde_json::json!({
+        "channel_type": "GROUP",
+        "type": 255,
+        "target_id": target_id,
+        "author_id": "1",
+        "content": "[系统消息]",
+        "extra": extra,
+        "msg_id": "xxx",
+        "msg_timestamp": 1612778254192u64,
+        "nonce": ""
+    }))
+    .unwrap()
+}
+
+/// `user` adding the `smile` reaction to `msg` in `channel` of `guild`.
+pub(crate) fn added_reaction() -> Event<EventExtra> {
+    system_event(
+        "guild",
+        serde_json::json!({
+            "type": "added_reaction",
+            "body": {
+                "channel_id": "channel",
+                "emoji": { "id": "1", "name": "smile" },
+                "user_id": "user",
+                "msg_id": "msg"
+            }
+        }),
+    )
+}
+
+/// A kmarkdown message from `user` in `channel` of `guild`, mentioning the
+/// users `(met)` in `content`.
+pub(crate) fn group_message(content: &str) -> Event<EventExtra> {
+    let mention: Vec<String> = crate::kmd_from_str(content)
+        .into_iter()
+        .filter_map(|item| match item {
+            crate::KMDItem::Mention(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+    serde_json::from_value(serde_json::json!({
+        "channel_type": "GROUP",
+        "type": 9,
+        "target_id": "channel",
+        "author_id": "user",
+        "content": content,
+        "msg_id": "xxx",
+        "msg_timestamp": 1607679133000u64,
+        "nonce": "",
+        "extra": {
+            "type": 9,
+            "guild_id": "guild",
+            "channel_name": "general",
+            "mention": mention,
+            "mention_all": false,
+            "mention_roles": [],
+            "mention_here": false,
+            "author": user("user")
+        }
+    }))
+    .unwrap()
+}
+
+/// `user_id` clicking the button with `value` on `msg` in `channel` of `guild`.
+pub(crate) fn button_click(value: &str, user_id: &str) -> Event<EventExtra> {
+    // clicks always come as direct events to the bot
+    let mut event = system_event(
+        "bot",
+        serde_json::json!({
+            "type": "message_btn_click",
+            "body": {
+                "msg_id": "msg",
+                "user_id": user_id,
+                "value": value,
+                "target_id": "channel",
+                "channel_type": "GROUP",
+                "guild_id": "guild",
+                "user_info": user(user_id)
+            }
+        }),
+    );
+    event.channel_type = EventChannelType::Person;
+    event
+}
+
+/// A user object as KOOK sends it.
+pub(crate) fn user(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "username": "name",
+        "nickname": "name",
+        "identify_num": "1234",
+        "online": true,
+        "bot": false,
+        "status": 1,
+        "avatar": "",
+        "vip_avatar": "",
+        "roles": []
+    })
+}
+
 pub struct EchoHandler;
 
 #[async_trait::async_trait]
 impl EventHandler for EchoHandler {
-    async fn handle_group_message_event(&self, khl: &Kook, event: Event<GroupMessageExtra>) {
+    async fn handle_group_message_event(
+        &self,
+        ctx: &Context,
+        event: Event<GroupMessageExtra>,
+    ) -> HandlerResult {
         let msg = event.content.clone();
         if msg.starts_with("echo") {
-            khl.create_message(None, &event.target_id, &msg, None, None, None)
-                .await
-                .unwrap();
+            ctx.create_message(None, &event.target_id, &msg, None, None, None)
+                .await?;
         }
+        Ok(())
     }
-    async fn handle_person_message_event(&self, khl: &Kook, event: Event<PersonMessageExtra>) {
+    async fn handle_person_message_event(
+        &self,
+        ctx: &Context,
+        event: Event<PersonMessageExtra>,
+    ) -> HandlerResult {
         if event.content.starts_with("echo") {
-            khl.create_direct_message(
+            ctx.create_direct_message(
                 Some(&event.author_id),
                 None,
                 &event.content,
@@ -41,9 +152,9 @@ impl EventHandler for EchoHandler {
                 None,
                 None,
             )
-            .await
-            .unwrap();
+            .await?;
         }
+        Ok(())
     }
 }
 
@@ -0,0 +1,212 @@
+use serde::de::{Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Milliseconds since unix epoch, as KOOK sends them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(i64);
+
+/// The timestamp is beyond what the target date type can hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("timestamp {0} is out of range")]
+pub struct TimestampOutOfRange(pub Timestamp);
+
+impl Timestamp {
+    pub fn from_millis(millis: i64) -> Self {
+        Self(millis)
+    }
+
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    pub fn as_millis(&self) -> i64 {
+        self.0
+    }
+
+    pub fn as_secs(&self) -> i64 {
+        self.0.div_euclid(1000)
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        (*self).into()
+    }
+
+    /// `None` if out of chrono's range.
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        (*self).try_into().ok()
+    }
+
+    /// `None` if out of time's range.
+    #[cfg(feature = "time")]
+    pub fn to_offset_date_time(&self) -> Option<time::OffsetDateTime> {
+        (*self).try_into().ok()
+    }
+}
+
+impl From<i64> for Timestamp {
+    fn from(millis: i64) -> Self {
+        Self(millis)
+    }
+}
+
+impl From<Timestamp> for i64 {
+    fn from(ts: Timestamp) -> Self {
+        ts.0
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(ts: Timestamp) -> Self {
+        if ts.0 >= 0 {
+            UNIX_EPOCH + Duration::from_millis(ts.0 as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_millis(ts.0.unsigned_abs())
+        }
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => Self(d.as_millis() as i64),
+            Err(e) => Self(-(e.duration().as_millis() as i64)),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Timestamp> for chrono::DateTime<chrono::Utc> {
+    type Error = TimestampOutOfRange;
+
+    fn try_from(ts: Timestamp) -> Result<Self, Self::Error> {
+        use chrono::TimeZone;
+        chrono::Utc
+            .timestamp_millis_opt(ts.0)
+            .single()
+            .ok_or(TimestampOutOfRange(ts))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Timestamp {
+    fn from(time: chrono::DateTime<Tz>) -> Self {
+        Self(time.timestamp_millis())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<Timestamp> for time::OffsetDateTime {
+    type Error = TimestampOutOfRange;
+
+    fn try_from(ts: Timestamp) -> Result<Self, Self::Error> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(ts.0 as i128 * 1_000_000)
+            .map_err(|_| TimestampOutOfRange(ts))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for Timestamp {
+    fn from(time: time::OffsetDateTime) -> Self {
+        Self((time.unix_timestamp_nanos() / 1_000_000) as i64)
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+struct TimestampVisitor;
+
+impl<'de> Visitor<'de> for TimestampVisitor {
+    type Value = Timestamp;
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("millisecond timestamp as int or string")
+    }
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Timestamp(v))
+    }
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        i64::try_from(v)
+            .map(Timestamp)
+            .map_err(|_| E::custom("timestamp out of range"))
+    }
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        v.parse().map(Timestamp).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        d.deserialize_any(TimestampVisitor)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i64(self.0)
+    }
+}
+
+#[test]
+fn timestamp_de_test() {
+    let ts: Timestamp = serde_json::from_str("1612778254183").unwrap();
+    assert_eq!(ts.as_millis(), 1612778254183);
+    assert_eq!(ts.as_secs(), 1612778254);
+    let ts2: Timestamp = serde_json::from_str(r#""1612778254183""#).unwrap();
+    assert_eq!(ts, ts2);
+    assert_eq!(serde_json::to_string(&ts).unwrap(), "1612778254183");
+    assert_eq!(Timestamp::from(ts.to_system_time()), ts);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn timestamp_chrono_test() {
+    let ts = Timestamp::from_millis(1612778254183);
+    let dt = ts.to_chrono().unwrap();
+    assert_eq!(dt.timestamp_millis(), 1612778254183);
+    assert_eq!(Timestamp::from(dt), ts);
+
+    let max: Timestamp = serde_json::from_str(r#""9223372036854775807""#).unwrap();
+    assert!(max.to_chrono().is_none());
+    assert_eq!(
+        chrono::DateTime::<chrono::Utc>::try_from(max),
+        Err(TimestampOutOfRange(max))
+    );
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn timestamp_time_test() {
+    let ts = Timestamp::from_millis(1612778254183);
+    let dt = ts.to_offset_date_time().unwrap();
+    assert_eq!(dt.unix_timestamp(), 1612778254);
+    assert_eq!(dt.millisecond(), 183);
+    assert_eq!(Timestamp::from(dt), ts);
+
+    let max: Timestamp = serde_json::from_str(r#""9223372036854775807""#).unwrap();
+    assert!(max.to_offset_date_time().is_none());
+    assert_eq!(
+        time::OffsetDateTime::try_from(Timestamp::from_millis(i64::MIN)),
+        Err(TimestampOutOfRange(Timestamp::from_millis(i64::MIN)))
+    );
+}